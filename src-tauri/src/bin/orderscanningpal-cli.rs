@@ -0,0 +1,4 @@
+// console companion to the app for engineering and scripted checks, shares its settings and data folder
+fn main() -> std::process::ExitCode {
+    orderscanningpal_lib::run_cli()
+}
@@ -0,0 +1,115 @@
+use serde::{Serialize, Deserialize};
+use crate::error::AppError;
+
+// Code 39 character values, mod 43 picks its check character from these
+const CODE39: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%";
+
+// check character added to the end of every printed serial, one scheme for the whole app set in the settings
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckDigit {
+    #[default]
+    None,
+    Mod10, // GS1 weighting, 3 and 1 from the right, same as GTINs
+    Luhn,
+    Mod43, // Code 39
+}
+
+impl CheckDigit {
+    // check character for digits, None when there is no scheme or the input has characters the scheme can't weigh
+    pub fn compute(self, digits: &str) -> Option<char> {
+        match self {
+            CheckDigit::None => None,
+            CheckDigit::Mod10 => {
+                let sum = weighted(digits, |i, d| if i % 2 == 0 { d * 3 } else { d })?;
+                char::from_digit((10 - sum % 10) % 10, 10)
+            }
+            CheckDigit::Luhn => {
+                let sum = weighted(digits, |i, d| if i % 2 == 0 { (d * 2) / 10 + (d * 2) % 10 } else { d })?;
+                char::from_digit((10 - sum % 10) % 10, 10)
+            }
+            CheckDigit::Mod43 => {
+                let sum = digits
+                    .chars()
+                    .map(|c| CODE39.iter().position(|&v| v as char == c.to_ascii_uppercase()))
+                    .sum::<Option<usize>>()?;
+                Some(CODE39[sum % 43] as char)
+            }
+        }
+    }
+
+    // the serial as printed, what the barcode and the human readable text both show
+    pub fn append(self, serial: &str) -> String {
+        match self.compute(serial) {
+            Some(check) => format!("{}{}", serial, check),
+            None => serial.to_string(),
+        }
+    }
+
+    // a scanned or typed serial back to the one in the tracker, an error when its check character is wrong
+    pub fn strip(self, scanned: &str) -> Result<String, AppError> {
+        // a mod 43 check character can be a space, only the scanner's line ending comes off the end
+        let scanned = scanned.trim_start().trim_end_matches(['\r', '\n', '\t']);
+        if self == CheckDigit::None {
+            return Ok(scanned.to_string());
+        }
+
+        let invalid = || AppError::serial("serial_check_digit", format!("'{}' doesn't end in a valid {:?} check character", scanned, self));
+        let (serial, check) = match scanned.char_indices().last() {
+            Some((at, check)) if at > 0 => (&scanned[..at], check),
+            _ => return Err(invalid()),
+        };
+        match self.compute(serial) {
+            Some(expected) if expected.eq_ignore_ascii_case(&check) => Ok(serial.to_string()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+// sum of the digits weighted by position counted from the right, 0 being the digit next to the check digit
+fn weighted(digits: &str, weight: impl Fn(usize, u32) -> u32) -> Option<u32> {
+    if digits.is_empty() {
+        return None;
+    }
+    digits
+        .chars()
+        .rev()
+        .enumerate()
+        .map(|(i, c)| c.to_digit(10).map(|d| weight(i, d)))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_check_digits() {
+        assert_eq!(CheckDigit::Mod10.compute("629104150021"), Some('3')); // GTIN-13 6291041500213
+        assert_eq!(CheckDigit::Mod10.compute("0001234560001"), Some('2'));
+        assert_eq!(CheckDigit::Luhn.compute("7992739871"), Some('3'));
+        assert_eq!(CheckDigit::Mod43.compute("12345"), Some('F'));
+        assert_eq!(CheckDigit::Mod43.compute("CODE39"), Some('W'));
+        assert_eq!(CheckDigit::Mod43.compute("999999999"), Some(' '));
+        assert_eq!(CheckDigit::None.compute("001360555"), None);
+        assert_eq!(CheckDigit::Luhn.compute("00136O555"), None);
+    }
+
+    #[test]
+    fn printed_serials_strip_back() {
+        for scheme in [CheckDigit::None, CheckDigit::Mod10, CheckDigit::Luhn, CheckDigit::Mod43] {
+            let printed = scheme.append("001360555");
+            assert_eq!(printed.len(), if scheme == CheckDigit::None { 9 } else { 10 });
+            assert_eq!(scheme.strip(&format!(" {}\r\n", printed)).unwrap(), "001360555", "{:?}", scheme);
+        }
+        assert_eq!(CheckDigit::Mod43.strip("999999999 \r\n").unwrap(), "999999999");
+    }
+
+    #[test]
+    fn wrong_check_digits_are_rejected() {
+        assert_eq!(CheckDigit::Luhn.append("001360555"), "0013605555");
+        for scanned in ["0013605554", "001360555", "3", ""] {
+            assert_eq!(CheckDigit::Luhn.strip(scanned).unwrap_err().code, "serial_check_digit", "{:?}", scanned);
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use std::env;
+use std::process::ExitCode;
+use serde::Serialize;
+use crate::context::AppContext;
+use crate::logging;
+use crate::sql;
+use crate::print;
+use crate::printer_health;
+use crate::notes::{self, NoteKind};
+use crate::serial_number::SerialNumber;
+use crate::serial_number_files;
+use crate::structs::{Order, OrderQuery, OrderSort, PrintBatch, PrintOrder, PrintOrderRow, PrintOutcome};
+use crate::error::AppError;
+
+const USAGE: &str = "usage: orderscanningpal-cli <command> [options]
+
+commands:
+  orders [options]                list open orders, newest first
+      --search <text>             order number, shop order or part number containing <text>
+      --status <status|any>       one of the statuses the query config allows, any by default
+      --due-from <YYYY-MM-DD>     due on or after
+      --due-to <YYYY-MM-DD>       due on or before
+      --series <5,7>              order number prefixes
+      --family <02,K02>           part number prefixes
+      --sort <order|part|due|quantity> [--asc]
+      --page <n> --page-size <n>  page through the list, 25 at a time by default
+  items <order>                   show the print items for an order
+  print <order> <rows> [options]  print rows by number (e.g. 1,4,5) or a packet:
+                                  starting, labels, final or all
+      --user <initials>           name written to the serial number tracker
+      --serial <serial>           first serial number, defaults to the current one
+      --quantity <n>              units to print, defaults to the units not printed yet
+      --start <n>                 first unit of the order to print, counting from 0
+      --supervisor <initials>     approve printing past the due quantity
+      --reprint                   don't advance the serial number or write the tracker
+  preview <order> <row> [--serial <serial>]
+                                  export one unit of a label or Final DOCS row to a PDF
+                                  instead of printing it, prints the file's path
+  serial                          show the next serial number
+  serial advance <serial>         move the next serial number forward to <serial>
+  serial reset-check              run the weekly / yearly serial reset
+  lookup-serial <serial>          show who printed a serial number and for which part
+  validate-settings               check folders, report files and printers in the settings
+  printers                        show the state of the default printer and every printer in the settings
+  lint-notes                      check every label and docs note in the ERP
+  query-config                    show which orders count as open and printable
+
+add --json to any command for machine readable output";
+
+// parsed `--name value` options and `--flag` switches, everything else is positional
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(raw: Vec<String>) -> Self {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut raw = raw.into_iter().peekable();
+        while let Some(arg) = raw.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if matches!(name, "json" | "reprint" | "asc") => options.push((name.to_string(), None)),
+                Some(name) => options.push((name.to_string(), raw.next_if(|next| !next.starts_with("--")))),
+                None => positional.push(arg),
+            }
+        }
+        Args { positional, options }
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(n, _)| n == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options.iter().find(|(n, _)| n == name).and_then(|(_, v)| v.as_deref())
+    }
+
+    fn number(&self, name: &str) -> Result<Option<u32>, AppError> {
+        self.value(name)
+            .map(|v| v.parse().map_err(|_| AppError::config("cli_usage", format!("--{} '{}' is not a number", name, v))))
+            .transpose()
+    }
+
+    fn list(&self, name: &str) -> Vec<String> {
+        self.value(name)
+            .map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect())
+            .unwrap_or_default()
+    }
+
+    fn required(&self, index: usize, name: &str) -> Result<&str, AppError> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| AppError::config("cli_usage", format!("Missing <{}>, see --help", name)))
+    }
+}
+
+pub fn run() -> ExitCode {
+    let args = Args::parse(env::args().skip(1).collect());
+    let command = args.positional.first().cloned().unwrap_or_default();
+    if command.is_empty() || command == "help" || args.flag("help") {
+        println!("{}", USAGE);
+        return if command.is_empty() && !args.flag("help") { ExitCode::from(2) } else { ExitCode::SUCCESS };
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start runtime: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = runtime.block_on(async {
+        let ctx = AppContext::standalone()?;
+        if let Err(e) = logging::init_in(&ctx) {
+            eprintln!("Failed to start logging: {}", e);
+        }
+        match command.as_str() {
+            "orders" => orders(&args, &ctx).await,
+            "items" => items(&args, &ctx).await,
+            "print" => print_rows(&args, &ctx).await,
+            "preview" => preview(&args, &ctx).await,
+            "serial" => serial(&args, &ctx).await,
+            "lookup-serial" => lookup_serial(&args, &ctx),
+            "validate-settings" => validate_settings(&args, &ctx),
+            "printers" => printers(&args, &ctx).await,
+            "lint-notes" => lint_notes(&args, &ctx).await,
+            "query-config" => query_config(&ctx),
+            other => Err(AppError::config("cli_usage", format!("Unknown command '{}', see --help", other))),
+        }
+    });
+
+    match result {
+        Ok(code) => code,
+        Err(e) if e.code == "cli_usage" => {
+            eprintln!("{}", e.message);
+            ExitCode::from(2)
+        }
+        Err(e) => {
+            if args.flag("json") {
+                let _ = print_json(&e);
+            } else {
+                eprintln!("{}", e);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// the orders options as the query the welcome page would send
+fn order_query(args: &Args) -> Result<OrderQuery, AppError> {
+    let defaults = OrderQuery::default();
+    Ok(OrderQuery {
+        search: args.value("search").unwrap_or_default().to_string(),
+        status: args.value("status").map(String::from),
+        due_from: args.value("due-from").map(String::from),
+        due_to: args.value("due-to").map(String::from),
+        series: args.list("series"),
+        families: args.list("family"),
+        sort: match args.value("sort") {
+            None | Some("order") => OrderSort::OrderNumber,
+            Some("part") => OrderSort::PartNumber,
+            Some("due") => OrderSort::DueDate,
+            Some("quantity") => OrderSort::DueQuantity,
+            Some(other) => return Err(AppError::config("cli_usage", format!("Can't sort by '{}', see --help", other))),
+        },
+        descending: !args.flag("asc"),
+        // pages are numbered from 1 on the command line
+        page: args.number("page")?.unwrap_or(1).saturating_sub(1),
+        page_size: args.number("page-size")?.unwrap_or(defaults.page_size),
+    })
+}
+
+async fn orders(args: &Args, ctx: &AppContext) -> Result<ExitCode, AppError> {
+    let page = ctx.erp().orders(&ctx.query_config()?, order_query(args)?).await?;
+    if args.flag("json") {
+        print_json(&page)?;
+    } else {
+        for order in &page.orders {
+            let due_date = order.due_date.map(|d| d.to_string()).unwrap_or_default();
+            println!("{}\t{}\t{}\t{}\t{}\t{}", order.order_number.trim(), order.part_number.trim(), order.assn_number.trim(), order.due_quantity, due_date, order.customer);
+        }
+        let first = page.page * page.page_size;
+        println!("{}-{} of {} orders", (first + 1).min(page.total), first + page.orders.len() as u32, page.total);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn items(args: &Args, ctx: &AppContext) -> Result<ExitCode, AppError> {
+    let order_number = args.required(1, "order")?;
+    let items = sql::print_items(order_number.to_string(), &ctx.settings()?, &ctx.query_config()?, ctx.erp()).await?;
+    if args.flag("json") {
+        print_json(&items)?;
+    } else {
+        // numbered the same way as the rows in the window
+        for (i, item) in items.iter().enumerate() {
+            println!("{:>3}  {:<16}{}", i + 1, item.print_type.trim(), item.notes.trim());
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+// the order as the window gets it, trimmed
+async fn load_order(order_number: &str, ctx: &AppContext) -> Result<Order, AppError> {
+    let mut order: Order = ctx.erp()
+        .order_number_info(&ctx.query_config()?, order_number.to_string())
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::not_found("order_not_found", format!("No open order {}", order_number)).with_order(order_number))?;
+    order.order_number = order.order_number.trim().to_string();
+    order.part_number = order.part_number.trim().to_string();
+    order.assn_number = order.assn_number.trim().to_string();
+    Ok(order)
+}
+
+async fn print_rows(args: &Args, ctx: &AppContext) -> Result<ExitCode, AppError> {
+    let order_number = args.required(1, "order")?;
+    let selection = args.required(2, "rows")?;
+
+    let rules = ctx.query_config()?;
+    let order = load_order(order_number, ctx).await?;
+    let batch = PrintBatch {
+        quantity: args.number("quantity")?,
+        start: args.number("start")?,
+        supervisor: args.value("supervisor").map(String::from),
+    };
+
+    let items = sql::print_items(order.order_number.clone(), &ctx.settings()?, &rules, ctx.erp()).await?;
+    let rows = select_rows(&items, selection)?;
+    let serial_number = match args.value("serial") {
+        Some(serial) => SerialNumber::parse(serial)?,
+        None => serial_number_files::internal_get_serial_number(ctx).await?,
+    };
+    let user = args.value("user").unwrap_or_default().to_string();
+    // 7 orders are reprints, same default as the window
+    let reprint_run = args.flag("reprint") || order.order_number.starts_with('7');
+
+    let mut results = Vec::new();
+    let mut failed = false;
+    for row in rows {
+        let print_type = row.print_type.clone();
+        let result = print::print_row(order.clone(), row, user.clone(), serial_number, reprint_run, batch.clone(), ctx).await;
+        // a copy missing from the archive needs attention too
+        failed |= match &result {
+            Ok(PrintOutcome::Printed { archive_failures, .. }) => !archive_failures.is_empty(),
+            Ok(PrintOutcome::SkippedNotFound { .. }) => false,
+            Err(_) => true,
+        };
+        results.push(RowResult { print_type, result });
+    }
+
+    if args.flag("json") {
+        print_json(&results)?;
+    } else {
+        for r in &results {
+            match &r.result {
+                Ok(PrintOutcome::Printed { copies, archive_failures }) => {
+                    println!("printed  {}  ({} copies)", r.print_type, copies);
+                    for e in archive_failures {
+                        println!("         not archived: {}", e);
+                    }
+                }
+                Ok(PrintOutcome::SkippedNotFound { root, term }) => println!("skipped  {}  nothing matching {} in {}", r.print_type, term, root),
+                Err(e) => println!("failed   {}  {}", r.print_type, e),
+            }
+        }
+    }
+    Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+async fn preview(args: &Args, ctx: &AppContext) -> Result<ExitCode, AppError> {
+    let order_number = args.required(1, "order")?;
+    let selection = args.required(2, "row")?;
+
+    let order = load_order(order_number, ctx).await?;
+    let items = sql::print_items(order.order_number.clone(), &ctx.settings()?, &ctx.query_config()?, ctx.erp()).await?;
+    let mut rows = select_rows(&items, selection)?;
+    if rows.len() != 1 {
+        return Err(AppError::config("cli_usage", "preview takes a single row number"));
+    }
+    let row = rows.remove(0);
+    let serial_number = match args.value("serial") {
+        Some(serial) => SerialNumber::parse(serial)?,
+        None => serial_number_files::internal_get_serial_number(ctx).await?,
+    };
+
+    let pdf = print::render_preview(order, row, serial_number, ctx)?;
+    if args.flag("json") {
+        print_json(&pdf)?;
+    } else {
+        println!("{}", pdf.display());
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize)]
+struct RowResult {
+    print_type: String,
+    result: Result<PrintOutcome, AppError>,
+}
+
+fn select_rows(items: &[PrintOrder], selection: &str) -> Result<Vec<PrintOrderRow>, AppError> {
+    let row = |i: usize, item: &PrintOrder| PrintOrderRow {
+        id: i as i32 + 1,
+        print_type: item.print_type.trim().to_string(),
+        notes: item.notes.trim().to_string(),
+    };
+    let in_packet = |i: usize, item: &PrintOrder| match selection {
+        // BOM, Config and SNL are always the first three rows
+        "starting" => i < 3 || NoteKind::of(&item.print_type) == Some(NoteKind::InitialDocs),
+        "labels" => NoteKind::of(&item.print_type) == Some(NoteKind::Label),
+        "final" => NoteKind::of(&item.print_type) == Some(NoteKind::FinalDocs),
+        _ => true,
+    };
+
+    if matches!(selection, "starting" | "labels" | "final" | "all") {
+        return Ok(items.iter().enumerate().filter(|(i, item)| in_packet(*i, item)).map(|(i, item)| row(i, item)).collect());
+    }
+
+    selection
+        .split(',')
+        .map(|n| {
+            let index = n.trim().parse::<usize>().ok().filter(|n| (1..=items.len()).contains(n))
+                .ok_or_else(|| AppError::config("cli_usage", format!("Row '{}' is not between 1 and {}", n, items.len())))?;
+            Ok(row(index - 1, &items[index - 1]))
+        })
+        .collect()
+}
+
+async fn serial(args: &Args, ctx: &AppContext) -> Result<ExitCode, AppError> {
+    match args.positional.get(1).map(String::as_str) {
+        None | Some("show") => (),
+        Some("advance") => {
+            let serial_number = args.required(2, "serial")?;
+            serial_number_files::serial_number_up(SerialNumber::parse(serial_number)?, ctx).await?;
+        }
+        Some("reset-check") => serial_number_files::handle_serial(ctx).await?,
+        Some(other) => return Err(AppError::config("cli_usage", format!("Unknown serial command '{}', see --help", other))),
+    }
+
+    let current = serial_number_files::internal_get_serial_number(ctx).await?.to_string();
+    if args.flag("json") {
+        print_json(&current)?;
+    } else {
+        println!("{}", current);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn lookup_serial(args: &Args, ctx: &AppContext) -> Result<ExitCode, AppError> {
+    let serial_number = args.required(1, "serial")?;
+    let entries = serial_number_files::lookup_serial(serial_number, ctx)?;
+    if args.flag("json") {
+        print_json(&entries)?;
+    } else {
+        for e in &entries {
+            println!("{}\t{}\t{}\t{}\t{}", e.date, e.part_number, e.assn_number, e.serial_number, e.user);
+        }
+        if entries.is_empty() {
+            println!("{} has not been printed", serial_number);
+        }
+    }
+    Ok(if entries.is_empty() { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+fn validate_settings(args: &Args, ctx: &AppContext) -> Result<ExitCode, AppError> {
+    let problems = ctx.settings()?.validate();
+    if args.flag("json") {
+        print_json(&problems)?;
+    } else {
+        for p in &problems {
+            println!("{}", p);
+        }
+        println!("{} problems", problems.len());
+    }
+    Ok(if problems.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+async fn printers(args: &Args, ctx: &AppContext) -> Result<ExitCode, AppError> {
+    let printers = printer_health::dashboard(&ctx.settings()?).await;
+    if args.flag("json") {
+        print_json(&printers)?;
+    } else {
+        for p in &printers {
+            println!("{}\t{}\t{:?}\t{}", p.role, p.name, p.state, p.problems.join(", "));
+        }
+    }
+    Ok(if printers.iter().all(|p| p.problems.is_empty()) { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+async fn lint_notes(args: &Args, ctx: &AppContext) -> Result<ExitCode, AppError> {
+    let report = notes::lint_all(ctx).await?;
+
+    if args.flag("json") {
+        print_json(&report)?;
+    } else {
+        for p in &report.problems {
+            println!("{}\t{}\t{}", p.part_number, p.print_type, p.problem);
+        }
+        println!("{} notes checked, {} problems", report.checked, report.problems.len());
+    }
+
+    // non-zero so scripts can fail a check on bad notes
+    Ok(if report.problems.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+fn query_config(ctx: &AppContext) -> Result<ExitCode, AppError> {
+    // already JSON, the file planners edit
+    print_json(&ctx.query_config()?)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::io("json_write", format!("Failed to serialize to JSON: {}", e)))?;
+    println!("{}", json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Args {
+        Args::parse(line.split_whitespace().map(String::from).collect())
+    }
+
+    #[test]
+    fn args_split_into_positionals_options_and_flags() {
+        let parsed = args("print 5000001 labels --user JS --reprint --quantity 4 --json");
+
+        assert_eq!(parsed.positional, ["print", "5000001", "labels"]);
+        assert_eq!(parsed.value("user"), Some("JS"));
+        assert_eq!(parsed.number("quantity").unwrap(), Some(4));
+        assert!(parsed.flag("reprint") && parsed.flag("json"));
+        assert!(!parsed.flag("asc"));
+        assert_eq!(parsed.required(2, "rows").unwrap(), "labels");
+        assert_eq!(parsed.required(3, "serial").unwrap_err().code, "cli_usage");
+    }
+
+    #[test]
+    fn options_without_a_value_stay_empty() {
+        // --supervisor runs into the next option instead of taking it as its value
+        let parsed = args("print 5000001 1 --supervisor --user JS");
+
+        assert!(parsed.flag("supervisor"));
+        assert_eq!(parsed.value("supervisor"), None);
+        assert_eq!(parsed.value("user"), Some("JS"));
+        assert_eq!(args("print --quantity four").number("quantity").unwrap_err().code, "cli_usage");
+    }
+
+    #[test]
+    fn orders_options_make_the_query() {
+        let query = order_query(&args("orders --search SO-1 --status 3 --due-to 2025-09-30 --series 5,,7 --family K02 --sort due --asc --page 2 --page-size 10")).unwrap();
+
+        assert_eq!(query.search, "SO-1");
+        assert_eq!(query.status.as_deref(), Some("3"));
+        assert_eq!((query.due_from, query.due_to.as_deref()), (None, Some("2025-09-30")));
+        assert_eq!((query.series, query.families), (vec!["5".to_string(), "7".to_string()], vec!["K02".to_string()]));
+        assert!(matches!(query.sort, OrderSort::DueDate));
+        assert!(!query.descending);
+        // pages count from 1 on the command line
+        assert_eq!((query.page, query.page_size), (1, 10));
+
+        let defaults = order_query(&args("orders")).unwrap();
+        assert!(matches!(defaults.sort, OrderSort::OrderNumber));
+        assert!(defaults.descending);
+        assert_eq!((defaults.page, defaults.page_size), (0, OrderQuery::default().page_size));
+        assert_eq!(order_query(&args("orders --sort customer")).err().unwrap().code, "cli_usage");
+    }
+
+    fn items(print_types: &[&str]) -> Vec<PrintOrder> {
+        print_types
+            .iter()
+            .map(|print_type| PrintOrder {
+                order_number: "5000001".to_string(),
+                part_number: "02A000100".to_string(),
+                due_quantity: 10.0,
+                assn_number: "02A000100".to_string(),
+                print_type: print_type.to_string(),
+                notes: format!(" {} note ", print_type),
+            })
+            .collect()
+    }
+
+    fn selected(selection: &str) -> Vec<(i32, String)> {
+        let items = items(&["BOM", "Config", "SNL", "Initial DOCS", "94A000003", "K94A000005", "Final DOCS"]);
+        select_rows(&items, selection).unwrap().into_iter().map(|row| (row.id, row.print_type)).collect()
+    }
+
+    #[test]
+    fn rows_are_picked_by_packet() {
+        let ids = |selection: &str| selected(selection).into_iter().map(|(id, _)| id).collect::<Vec<_>>();
+
+        assert_eq!(ids("starting"), [1, 2, 3, 4]);
+        assert_eq!(ids("labels"), [5, 6]);
+        assert_eq!(ids("final"), [7]);
+        assert_eq!(ids("all"), [1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn rows_are_picked_by_number() {
+        assert_eq!(selected("5, 7"), [(5, "94A000003".to_string()), (7, "Final DOCS".to_string())]);
+
+        let items = items(&["BOM", "Config", "SNL"]);
+        let row = select_rows(&items, "2").unwrap().remove(0);
+        // ids are the window's 1 based row numbers, notes come trimmed
+        assert_eq!((row.id, row.notes.as_str()), (2, "Config note"));
+        for selection in ["0", "4", "two", "1,,2"] {
+            assert_eq!(select_rows(&items, selection).err().unwrap().code, "cli_usage", "{}", selection);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+use chrono::{DateTime, Local};
+use tauri::{AppHandle, Manager};
+use crate::settings::{self, Settings};
+use crate::query_config::{self, QueryConfig};
+use crate::sql::{DbConfig, Erp, SqlServer};
+use crate::error::AppError;
+
+// tauri.conf.json identifier, names the AppData folder
+const APP_IDENTIFIER: &str = "com.orderscanningpal.app";
+
+// local time, the shop's weeks and dates are local
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+// everything the backend needs from its surroundings: where its files live, the time and the ERP,
+// built from the window's AppHandle, standalone for the cli, or by hand in tests
+#[derive(Clone)]
+pub struct AppContext {
+    data_dir: PathBuf,
+    clock: Arc<dyn Clock>,
+    erp: Arc<dyn Erp>,
+}
+
+impl AppContext {
+    pub fn new(data_dir: PathBuf, clock: Arc<dyn Clock>, erp: Arc<dyn Erp>) -> Self {
+        AppContext { data_dir, clock, erp }
+    }
+
+    pub fn from_app(app_handle: &AppHandle) -> Result<Self, AppError> {
+        // if the DOC_PATH is build,  we navigate AppData, else go to the .env location
+        let data_dir = if env!("DOC_PATH") == "build" {
+            app_handle
+                .path()
+                .app_data_dir()
+                .map_err(|e| AppError::config("data_dir", format!("Failed to resolve app data folder: {}", e)))?
+        } else {
+            PathBuf::from(env!("DOC_PATH"))
+        };
+        Ok(Self::new(data_dir, Arc::new(SystemClock), Arc::new(SqlServer::new(DbConfig::from_env()))))
+    }
+
+    // same folder from_app resolves to, found without a running app
+    pub fn standalone() -> Result<Self, AppError> {
+        let data_dir = if env!("DOC_PATH") == "build" {
+            env::var_os("APPDATA")
+                .map(|dir| PathBuf::from(dir).join(APP_IDENTIFIER))
+                .ok_or_else(|| AppError::config("data_dir", "APPDATA is not set, can't find the app data folder"))?
+        } else {
+            PathBuf::from(env!("DOC_PATH"))
+        };
+        Ok(Self::new(data_dir, Arc::new(SystemClock), Arc::new(SqlServer::new(DbConfig::from_env()))))
+    }
+
+    pub fn file(&self, file_name: &str) -> PathBuf {
+        self.data_dir.join(file_name)
+    }
+
+    pub fn now(&self) -> DateTime<Local> {
+        self.clock.now()
+    }
+
+    pub fn erp(&self) -> &dyn Erp {
+        self.erp.as_ref()
+    }
+
+    pub fn settings(&self) -> Result<Settings, AppError> {
+        settings::load_settings_in(self)
+    }
+
+    pub fn query_config(&self) -> Result<QueryConfig, AppError> {
+        query_config::load(self)
+    }
+}
+
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use std::fs;
+    use crate::erp_fixture::FixtureErp;
+    use crate::structs::Order;
+
+    pub struct FixedClock(pub DateTime<Local>);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> DateTime<Local> {
+            self.0
+        }
+    }
+
+    // empty folder under the system temp dir, one per test name, over the seeded in-memory ERP
+    pub fn temp_context(name: &str, now: DateTime<Local>) -> AppContext {
+        temp_context_with(name, now, FixtureErp::seeded())
+    }
+
+    // the same over ERP data changed for the test
+    pub fn temp_context_with(name: &str, now: DateTime<Local>, erp: FixtureErp) -> AppContext {
+        let dir = env::temp_dir().join(format!("orderscanningpal-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temp dir should be writable");
+        AppContext::new(dir, Arc::new(FixedClock(now)), Arc::new(erp))
+    }
+
+    // order 5000001 for 02A000100 as the window sends it to print, without the list columns
+    pub fn order(due_quantity: f64) -> Order {
+        Order {
+            order_number: "5000001".to_string(),
+            order_number_full: "5000001".to_string(),
+            part_number: "02A000100".to_string(),
+            due_quantity,
+            assn_number: "02A000100".to_string(),
+            due_date: None,
+            release_date: None,
+            customer: String::new(),
+            planner: String::new(),
+            remaining_quantity: due_quantity,
+        }
+    }
+}
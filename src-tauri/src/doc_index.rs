@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use once_cell::sync::Lazy;
+use log::{debug, info, warn};
+use tauri::AppHandle;
+use crate::settings;
+use crate::error::AppError;
+
+// how long a root's index is trusted before directory timestamps are checked again
+const REFRESH_AFTER: Duration = Duration::from_secs(30);
+
+static INDEXES: Lazy<Mutex<HashMap<PathBuf, RootIndex>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct IndexedDir {
+    modified: Option<SystemTime>,
+    files: Vec<PathBuf>,
+    subdirs: Vec<PathBuf>,
+}
+
+struct RootIndex {
+    dirs: HashMap<PathBuf, IndexedDir>,
+    checked: Instant,
+}
+
+impl RootIndex {
+    fn build(root: &Path) -> Self {
+        let started = Instant::now();
+        let mut index = RootIndex { dirs: HashMap::new(), checked: Instant::now() };
+        index.scan(root.to_path_buf());
+        info!("indexed {} files under {} in {:?}", index.file_count(), root.display(), started.elapsed());
+        index
+    }
+
+    fn scan(&mut self, dir: PathBuf) {
+        // lists one directory, then any subdirectory not already indexed
+        let mut pending = vec![dir];
+        while let Some(dir) = pending.pop() {
+            let modified = fs::metadata(&dir).and_then(|m| m.modified()).ok();
+            let mut files = Vec::new();
+            let mut subdirs = Vec::new();
+            if let Ok(entries) = fs::read_dir(&dir) {
+                for entry in entries.filter_map(Result::ok) {
+                    match entry.file_type() {
+                        Ok(t) if t.is_dir() => subdirs.push(entry.path()),
+                        Ok(t) if t.is_file() => files.push(entry.path()),
+                        _ => (),
+                    }
+                }
+            }
+            for subdir in &subdirs {
+                if !self.dirs.contains_key(subdir) {
+                    pending.push(subdir.clone());
+                }
+            }
+            self.dirs.insert(dir, IndexedDir { modified, files, subdirs });
+        }
+    }
+
+    fn refresh(&mut self) {
+        // a directory's modified time changes when entries are added, removed or renamed in it
+        let known: Vec<PathBuf> = self.dirs.keys().cloned().collect();
+        for dir in known {
+            let Some(indexed) = self.dirs.get(&dir) else { continue };
+            match fs::metadata(&dir).and_then(|m| m.modified()) {
+                Ok(modified) if Some(modified) == indexed.modified => (),
+                Ok(_) => {
+                    debug!("re-indexing {}", dir.display());
+                    let old_subdirs = indexed.subdirs.clone();
+                    self.dirs.remove(&dir);
+                    self.scan(dir.clone());
+                    let new_subdirs = self.dirs.get(&dir).map(|d| d.subdirs.clone()).unwrap_or_default();
+                    for subdir in old_subdirs.iter().filter(|d| !new_subdirs.contains(d)) {
+                        self.remove_tree(subdir);
+                    }
+                }
+                Err(_) => self.remove_tree(&dir),
+            }
+        }
+        self.checked = Instant::now();
+    }
+
+    fn remove_tree(&mut self, dir: &Path) {
+        if let Some(indexed) = self.dirs.remove(dir) {
+            for subdir in indexed.subdirs {
+                self.remove_tree(&subdir);
+            }
+        }
+    }
+
+    fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.dirs.values().flat_map(|d| d.files.iter())
+    }
+
+    fn file_count(&self) -> usize {
+        self.dirs.values().map(|d| d.files.len()).sum()
+    }
+}
+
+// finds the one document under root_dir for search_term, None when nothing matches
+pub fn find_document(root_dir: &str, search_term: &str) -> Result<Option<PathBuf>, AppError> {
+    let root = PathBuf::from(root_dir);
+    if !root.is_dir() {
+        return Err(AppError::not_found("search_root_missing", format!("Search folder for '{}' does not exist", search_term))
+            .with_path(&root));
+    }
+
+    // a fresh index is searched in place, a stale one is taken out of the map
+    let stale = {
+        let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+        match indexes.get(&root) {
+            Some(index) if index.checked.elapsed() <= REFRESH_AFTER => {
+                return best_match(index.files(), search_term).map_err(|e| e.with_path(&root));
+            }
+            _ => indexes.remove(&root),
+        }
+    };
+
+    // scanned or refreshed without the lock, so searches under other roots and a rebuild don't wait on the share
+    let index = match stale {
+        Some(mut index) => {
+            index.refresh();
+            index
+        }
+        None => RootIndex::build(&root),
+    };
+    let found = best_match(index.files(), search_term).map_err(|e| e.with_path(&root));
+    INDEXES.lock().unwrap_or_else(|e| e.into_inner()).insert(root, index);
+    found
+}
+
+// builds the index for every configured root ahead of the first print
+pub fn warm(app_handle: &AppHandle) {
+    let app_settings = match settings::internal_load_settings(app_handle) {
+        Ok(s) => s.with_resolved_paths(),
+        Err(e) => {
+            warn!("skipping document index warm up: {}", e);
+            return;
+        }
+    };
+    for root in [app_settings.label_path, app_settings.config_path] {
+        let root = PathBuf::from(root);
+        if root.is_dir() {
+            let index = RootIndex::build(&root);
+            INDEXES.lock().unwrap_or_else(|e| e.into_inner()).insert(root, index);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rebuild_document_index(app_handle: AppHandle) -> Result<usize, AppError> {
+    INDEXES.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    tauri::async_runtime::spawn_blocking(move || warm(&app_handle))
+        .await
+        .map_err(|e| AppError::io("document_index", format!("Failed to rebuild document index: {}", e)))?;
+
+    let indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(indexes.values().map(|i| i.file_count()).sum())
+}
+
+fn best_match<'a>(files: impl Iterator<Item = &'a PathBuf>, search_term: &str) -> Result<Option<PathBuf>, AppError> {
+    let term = search_term.trim().to_lowercase();
+    if term.is_empty() {
+        return Ok(None);
+    }
+
+    let mut exact = Vec::new();
+    let mut prefix = Vec::new();
+    let mut contains = Vec::new();
+    for path in files {
+        let Some(stem) = path.file_stem().map(|s| s.to_string_lossy().to_lowercase()) else { continue };
+        if stem == term {
+            exact.push(path);
+        } else if stem.starts_with(&term) && !stem[term.len()..].starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            prefix.push(path);
+        } else if stem.contains(&term) {
+            contains.push(path);
+        }
+    }
+
+    // most specific tier wins, within a tier the highest revision wins
+    for candidates in [exact, prefix, contains] {
+        match candidates.len() {
+            0 => continue,
+            1 => return Ok(Some(candidates[0].clone())),
+            _ => return pick_revision(&candidates, &term).map(Some),
+        }
+    }
+    Ok(None)
+}
+
+fn pick_revision(candidates: &[&PathBuf], term: &str) -> Result<PathBuf, AppError> {
+    let mut ranked: Vec<(Option<(char, u32)>, &PathBuf)> = candidates
+        .iter()
+        .map(|path| {
+            let stem = path.file_stem().map(|s| s.to_string_lossy().to_lowercase()).unwrap_or_default();
+            let rest = stem.find(term).map(|i| &stem[i + term.len()..]).unwrap_or("");
+            (revision(rest), *path)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0));
+
+    // only unambiguous when one file carries the highest revision
+    if ranked[0].0.is_some() && ranked[0].0 != ranked[1].0 {
+        return Ok(ranked[0].1.clone());
+    }
+
+    let listed = candidates.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ");
+    Err(AppError::not_found("document_ambiguous", format!("{} documents match '{}': {}", candidates.len(), term, listed)))
+}
+
+fn revision(rest: &str) -> Option<(char, u32)> {
+    // "-a03 serial label" -> ('A', 3)
+    let mut chars = rest.strip_prefix('-')?.chars();
+    let letter = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    let digits: String = chars.take_while(|c| c.is_ascii_digit()).collect();
+    if digits.len() != 2 {
+        return None;
+    }
+    Some((letter.to_ascii_uppercase(), digits.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(|n| Path::new("labels").join(n)).collect()
+    }
+
+    fn found(names: &[&str], search_term: &str) -> Option<String> {
+        best_match(paths(names).iter(), search_term)
+            .unwrap()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+    }
+
+    #[test]
+    fn revisions_are_a_letter_and_two_digits() {
+        assert_eq!(revision("-a03 serial label"), Some(('A', 3)));
+        assert_eq!(revision("-B12"), Some(('B', 12)));
+        assert_eq!(revision("-a3"), None);
+        assert_eq!(revision("-a003"), None);
+        assert_eq!(revision("a03"), None);
+        assert_eq!(revision("-03"), None);
+        assert_eq!(revision(""), None);
+    }
+
+    #[test]
+    fn exact_names_win_over_longer_ones() {
+        let names = ["94A000003-A01.btw", "94A000003.btw", "old 94A000003.btw"];
+
+        assert_eq!(found(&names, "94A000003").as_deref(), Some("94A000003.btw"));
+    }
+
+    #[test]
+    fn part_numbers_match_whole_and_without_case() {
+        let names = ["94A0000031.btw", "94a000003 serial label.btw", "94A00000.btw"];
+
+        assert_eq!(found(&names, " 94A000003 ").as_deref(), Some("94a000003 serial label.btw"));
+        // a longer part number only contains the term
+        assert_eq!(found(&names[..1], "94A000003").as_deref(), Some("94A0000031.btw"));
+        assert_eq!(found(&names, "94A000009"), None);
+        assert_eq!(found(&names, "  "), None);
+    }
+
+    #[test]
+    fn highest_revision_wins() {
+        let names = ["94A000003-A10.btw", "94A000003-B02 box.btw", "94A000003-A02.btw", "94A000004-C01.btw"];
+
+        assert_eq!(found(&names, "94A000003").as_deref(), Some("94A000003-B02 box.btw"));
+        assert_eq!(found(&names[..1], "94A000003").as_deref(), Some("94A000003-A10.btw"));
+    }
+
+    #[test]
+    fn stale_indexes_pick_up_new_files() {
+        let root = std::env::temp_dir().join("orderscanningpal-doc-index-stale");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("labels")).unwrap();
+        let root_dir = root.to_string_lossy().to_string();
+
+        assert_eq!(find_document(&root_dir, "94A000003").unwrap(), None);
+        fs::write(root.join("labels").join("94A000003.btw"), "").unwrap();
+        // still trusted until REFRESH_AFTER, then the directory's new modified time is seen
+        assert_eq!(find_document(&root_dir, "94A000003").unwrap(), None);
+        let aged = Instant::now().checked_sub(REFRESH_AFTER * 2).unwrap();
+        INDEXES.lock().unwrap().get_mut(&root).unwrap().checked = aged;
+
+        assert_eq!(find_document(&root_dir, "94A000003").unwrap(), Some(root.join("labels").join("94A000003.btw")));
+        assert!(INDEXES.lock().unwrap().get(&root).unwrap().checked.elapsed() < REFRESH_AFTER);
+    }
+
+    #[test]
+    fn ties_and_missing_revisions_are_ambiguous() {
+        let cases = [
+            vec!["94A000003-A01.btw", "94A000003-a01 copy.btw"],
+            vec!["94A000003 box.btw", "94A000003 serial.btw"],
+        ];
+
+        for names in cases {
+            let files = paths(&names);
+            let candidates: Vec<&PathBuf> = files.iter().collect();
+            let e = pick_revision(&candidates, "94a000003").unwrap_err();
+            assert_eq!(e.code, "document_ambiguous", "{:?}", names);
+            assert!(best_match(files.iter(), "94A000003").is_err());
+        }
+    }
+}
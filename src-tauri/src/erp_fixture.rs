@@ -0,0 +1,360 @@
+use chrono::NaiveDate;
+use futures::future::BoxFuture;
+use crate::sql::{self, Erp};
+use crate::query_config::QueryConfig;
+use crate::error::AppError;
+use crate::structs::{NoteRow, Order, OrderPage, OrderQuery, OrderSort, PrintItemRow};
+
+// the ERP tables the queries in sql.rs read, held in memory so the SQL layer can be tested without the
+// production server. each query below follows its SQL in sql.rs join for join, change them together
+
+pub struct OrderMaster {
+    pub ordnum: &'static str, // ORDNUM_10
+    pub order: &'static str,  // ORDER_10
+    pub prtnum: &'static str, // PRTNUM_10
+    pub dueqty: f64,          // DUEQTY_10
+    pub planid: &'static str, // PLANID_10
+    pub status: &'static str, // STATUS_10
+    pub curdue: &'static str, // CURDUE_10, YYYY-MM-DD
+    pub reldte: &'static str, // RELDTE_10, YYYY-MM-DD
+    pub custid: &'static str, // CUSTID_10
+    pub cmpqty: f64,          // CMPQTY_10
+}
+
+pub struct RequirementDetail {
+    pub ordnum: &'static str, // ORDNUM_11
+    pub prtnum: &'static str, // PRTNUM_11
+}
+
+pub struct ProductStructure {
+    pub parprt: &'static str, // PARPRT_02
+    pub comprt: &'static str, // COMPRT_02
+}
+
+pub struct PartMaster {
+    pub prtnum: &'static str,      // PRTNUM_01
+    pub part_type: &'static str,   // TYPE_01, 'S' for a subassembly
+    pub description: &'static str, // PMDES1_01
+}
+
+pub struct WindowsNote {
+    pub maxid: u32,                   // MAXID
+    pub prtnum: &'static str,         // PRTNUM_61
+    pub comprt: &'static str,         // COMPRT_61
+    pub notes: Option<&'static str>,  // NOTES_61
+}
+
+#[derive(Default)]
+pub struct FixtureErp {
+    pub order_master: Vec<OrderMaster>,
+    pub requirement_detail: Vec<RequirementDetail>,
+    pub product_structure: Vec<ProductStructure>,
+    pub part_master: Vec<PartMaster>,
+    pub windows_notes: Vec<WindowsNote>,
+}
+
+impl FixtureErp {
+    // 5000001  02A000100 controller, labels and docs straight off the top part, a switched off, an empty and a
+    //          continued note, a standard parts kit
+    // 5000002  02B000200 rack built from the K02A000300 subassembly, notes and common parts come from the subassembly
+    // 7000003  reprint of the controller
+    // 6000004, 5000005, 5000006, 5000007 are left out of the open orders by prefix, planner 000, status and due qty
+    pub fn seeded() -> Self {
+        let order = |ordnum, order, prtnum, dueqty, planid, status, curdue, reldte, custid, cmpqty| {
+            OrderMaster { ordnum, order, prtnum, dueqty, planid, status, curdue, reldte, custid, cmpqty }
+        };
+        let required = |ordnum, prtnum| RequirementDetail { ordnum, prtnum };
+        let structure = |parprt, comprt| ProductStructure { parprt, comprt };
+        let part = |prtnum, part_type, description| PartMaster { prtnum, part_type, description };
+        let note = |maxid, prtnum, comprt, notes| WindowsNote { maxid, prtnum, comprt, notes };
+
+        let mut requirement_detail = vec![
+            required("5000002", "K94A000005"),
+            required("5000002", "94A000003"),
+            required("5000002", "Final DOCS"),
+            required("5000002", "10-0001"),
+        ];
+        for ordnum in ["5000001", "7000003", "6000004", "5000005", "5000006", "5000007"] {
+            requirement_detail.extend([
+                required(ordnum, "Initial DOCS"),
+                required(ordnum, "94A000003"),
+                required(ordnum, "94A000004-A01"),
+                required(ordnum, "94A000047-A01"),
+                required(ordnum, "Final DOCS"),
+                required(ordnum, "10-0001"),
+            ]);
+        }
+
+        FixtureErp {
+            order_master: vec![
+                order("5000001", "SO-1001", "02A000100", 10.0, "100", "3", "2025-09-05", "2025-08-25", "ACME", 0.0),
+                order("5000002", "SO-1002", "02B000200", 4.0, "200", "3", "2025-09-12", "2025-09-01", "NORTHWIND", 1.0),
+                order("7000003", "SO-1003", "02A000100", 2.0, "100", "3", "2025-08-29", "2025-08-27", "ACME", 0.0),
+                order("6000004", "SO-1004", "02A000100", 5.0, "100", "3", "2025-09-05", "2025-08-25", "ACME", 0.0),
+                order("5000005", "SO-1005", "02A000100", 5.0, "000", "3", "2025-09-05", "2025-08-25", "STOCK", 0.0),
+                order("5000006", "SO-1006", "02A000100", 5.0, "100", "2", "2025-09-19", "2025-09-10", "ACME", 0.0),
+                order("5000007", "SO-1007", "02A000100", 0.0, "100", "3", "2025-09-05", "2025-08-25", "ACME", 0.0),
+            ],
+            requirement_detail,
+            product_structure: vec![
+                structure("02A000100", "Initial DOCS"),
+                structure("02A000100", "94A000003"),
+                structure("02A000100", "94A000004-A01"),
+                structure("02A000100", "94A000047-A01"),
+                structure("02A000100", "Final DOCS"),
+                structure("02A000100", "02S000500"),
+                structure("02S000500", "10-0001"),
+                structure("02B000200", "K02A000300"),
+                structure("K02A000300", "K94A000005"),
+                structure("K02A000300", "94A000003"),
+                structure("K02A000300", "02S000600"),
+                structure("02S000600", "10-0001"),
+            ],
+            part_master: vec![
+                part("02A000100", "M", "Controller Assembly"),
+                part("02B000200", "M", "Rack Assembly"),
+                part("K02A000300", "S", "Rack Subassembly"),
+                part("02S000500", "M", "Standard Parts Kit"),
+                part("02S000600", "M", "Rack Common Parts"),
+                part("94A000003", "P", "Product Label"),
+                part("94A000004-A01", "P", "Box Label"),
+                part("94A000047-A01", "P", "Old Product Label"),
+                part("K94A000005", "P", "Rack Label"),
+                part("Initial DOCS", "P", "Initial Documents"),
+                part("Final DOCS", "P", "Final Documents"),
+                part("10-0001", "P", "Screw M3x8"),
+            ],
+            // out of MAXID order on purpose, the queries sort
+            windows_notes: vec![
+                note(50, "02A000100", "Final DOCS", Some("P:\\Final?FinalReport?100")),
+                note(10, "02A000100", "Initial DOCS", Some("P:\\Work Instructions?WI-100?clr")),
+                note(21, "02A000100", "94A000003", Some("?Lot 7")),
+                note(20, "02A000100", "94A000003", Some("ProductLabel?Rev B")),
+                note(30, "02A000100", "94A000047-A01", Some("~OldLabel")),
+                note(35, "02A000100", "94A000004-A01", Some("BoxLabel")),
+                note(40, "02A000100", "94A000003", None),
+                note(60, "K02A000300", "K94A000005", Some("RackLabel?K")),
+                note(61, "K02A000300", "94A000003", Some("   ")),
+                note(70, "K02A000300", "Final DOCS", Some("P:\\Final?RackReport")),
+                // on the rack itself, which has no labels of its own
+                note(80, "02B000200", "94A000003", Some("ShouldNotPrint")),
+            ],
+        }
+    }
+
+    fn part(&self, prtnum: &str) -> Option<&PartMaster> {
+        self.part_master.iter().find(|pm| pm.prtnum == prtnum)
+    }
+
+    // ps.PARPRT_02 = parent AND pm.PRTNUM_01 = ps.COMPRT_02
+    fn components<'a>(&'a self, parent: &'a str) -> impl Iterator<Item = (&'a ProductStructure, &'a PartMaster)> + 'a {
+        self.product_structure
+            .iter()
+            .filter(move |ps| ps.parprt == parent)
+            .filter_map(move |ps| self.part(ps.comprt).map(|pm| (ps, pm)))
+    }
+
+    // rd.ORDNUM_11 = ordnum
+    fn requirements<'a>(&'a self, ordnum: &'a str) -> impl Iterator<Item = &'a RequirementDetail> + 'a {
+        self.requirement_detail.iter().filter(move |rd| rd.ordnum == ordnum)
+    }
+
+    // (om.ORDNUM_10 = @P1 OR om.ORDER_10 = @P1) AND om.DUEQTY_10 > 0
+    fn lookup<'a>(&'a self, order_number: &'a str) -> impl Iterator<Item = &'a OrderMaster> + 'a {
+        self.order_master
+            .iter()
+            .filter(move |om| (om.ordnum == order_number || om.order == order_number) && om.dueqty > 0.0)
+    }
+}
+
+fn is_print_part(prtnum: &str) -> bool {
+    prtnum.starts_with("94A") || prtnum.starts_with("K94A") || prtnum == "Initial DOCS" || prtnum == "Final DOCS"
+}
+
+fn is_common(pm: &PartMaster) -> bool {
+    pm.description.contains("Standard Parts") || pm.description.contains("Common Parts")
+}
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").expect("fixture dates are YYYY-MM-DD")
+}
+
+fn due(om: &OrderMaster) -> NaiveDate {
+    date(om.curdue)
+}
+
+// the Order both order queries build from an Order_Master row
+fn order_from(om: &OrderMaster, order_number_full: &str, assn_number: &str) -> Order {
+    Order {
+        order_number: om.ordnum.to_string(),
+        order_number_full: order_number_full.to_string(),
+        part_number: om.prtnum.to_string(),
+        due_quantity: om.dueqty,
+        assn_number: assn_number.to_string(),
+        due_date: Some(due(om)),
+        release_date: Some(date(om.reldte)),
+        customer: om.custid.to_string(),
+        planner: om.planid.to_string(),
+        remaining_quantity: (om.dueqty - om.cmpqty).max(0.0),
+    }
+}
+
+// LIKE with the default case insensitive collation
+fn like(value: &str, text: &str) -> bool {
+    value.to_uppercase().contains(&text.to_uppercase())
+}
+
+fn starts_like(value: &str, prefix: &str) -> bool {
+    value.to_uppercase().starts_with(&prefix.trim().to_uppercase())
+}
+
+// a rule's prefix list, empty for any
+fn prefixed(value: &str, prefixes: &[String]) -> bool {
+    prefixes.is_empty() || prefixes.iter().any(|p| starts_like(value, p))
+}
+
+// a rule's IN list, empty for any
+fn listed(value: &str, values: &[String]) -> bool {
+    values.is_empty() || values.iter().any(|v| v.trim() == value)
+}
+
+// SELECT DISTINCT
+fn push_distinct(orders: &mut Vec<Order>, order: Order) {
+    if !orders.contains(&order) {
+        orders.push(order);
+    }
+}
+
+impl Erp for FixtureErp {
+    fn orders<'a>(&'a self, rules: &'a QueryConfig, query: OrderQuery) -> BoxFuture<'a, Result<OrderPage, AppError>> {
+        Box::pin(async move {
+            let (due_from, due_before) = sql::due_window(&query)?;
+            let page_size = sql::page_size(&query);
+
+            // open_orders
+            let mut open: Vec<(Order, &OrderMaster)> = Vec::new();
+            let open_rules = &rules.open_orders;
+            let status = open_rules.status_filter(query.status.as_deref())?;
+            let eligible = self.order_master.iter().filter(|om| {
+                prefixed(om.ordnum, &open_rules.order_prefixes)
+                    && om.dueqty > 0.0
+                    && (open_rules.excluded_planners.is_empty() || !listed(om.planid, &open_rules.excluded_planners))
+                    && listed(om.status, &open_rules.statuses)
+                    && status.as_deref().is_none_or(|status| om.status == status)
+            });
+            for om in eligible {
+                for rd in self.requirements(om.ordnum) {
+                    for (ps, pm) in self.components(om.prtnum) {
+                        let subassembly = pm.part_type == "S" && prefixed(pm.prtnum, &open_rules.part_prefixes);
+                        let required = rd.prtnum == ps.comprt && prefixed(ps.parprt, &open_rules.part_prefixes);
+                        let order = order_from(om, om.order, if pm.part_type == "S" { pm.prtnum } else { om.prtnum });
+                        if (subassembly || required) && !open.iter().any(|(o, _)| *o == order) {
+                            open.push((order, om));
+                        }
+                    }
+                }
+            }
+
+            let search = query.search.trim();
+            open.retain(|(o, om)| {
+                (search.is_empty() || [&o.order_number, &o.order_number_full, &o.part_number, &o.assn_number].iter().any(|v| like(v, search)))
+                    && due_from.is_none_or(|from| due(om) >= from)
+                    && due_before.is_none_or(|before| due(om) < before)
+                    && (query.series.is_empty() || query.series.iter().any(|p| starts_like(&o.order_number, p)))
+                    && (query.families.is_empty() || query.families.iter().any(|p| starts_like(&o.part_number, p) || starts_like(&o.assn_number, p)))
+            });
+
+            open.sort_by(|(a, a_om), (b, b_om)| {
+                let by = match query.sort {
+                    OrderSort::OrderNumber => a.order_number.cmp(&b.order_number),
+                    OrderSort::PartNumber => a.part_number.cmp(&b.part_number),
+                    OrderSort::DueDate => due(a_om).cmp(&due(b_om)),
+                    OrderSort::DueQuantity => a.due_quantity.total_cmp(&b.due_quantity),
+                };
+                let by = if query.descending { by.reverse() } else { by };
+                by.then_with(|| b.order_number.cmp(&a.order_number))
+            });
+
+            let total = open.len() as u32;
+            let orders = open
+                .into_iter()
+                .map(|(o, _)| o)
+                .skip(query.page as usize * page_size as usize)
+                .take(page_size as usize)
+                .collect();
+            Ok(OrderPage { orders, total, page: query.page, page_size })
+        })
+    }
+
+    fn order_number_info<'a>(&'a self, rules: &'a QueryConfig, order_number: String) -> BoxFuture<'a, Result<Vec<Order>, AppError>> {
+        let lookup = &rules.order_lookup;
+        let mut orders = Vec::new();
+        for om in self.lookup(&order_number).filter(|om| listed(om.status, &lookup.statuses)) {
+            for rd in self.requirements(om.ordnum) {
+                for (ps, pm) in self.components(om.prtnum) {
+                    let subassembly = pm.part_type == "S" && prefixed(pm.prtnum, &lookup.subassembly_prefixes);
+                    if subassembly || rd.prtnum == ps.comprt {
+                        push_distinct(&mut orders, order_from(om, om.ordnum, if pm.part_type == "S" { pm.prtnum } else { om.prtnum }));
+                    }
+                }
+            }
+        }
+        Box::pin(async move { Ok(orders) })
+    }
+
+    fn print_item_rows<'a>(&'a self, rules: &'a QueryConfig, order_number: String) -> BoxFuture<'a, Result<Vec<PrintItemRow>, AppError>> {
+        let lookup = &rules.print_items;
+        let mut rows = Vec::new();
+        for om in self.lookup(&order_number).filter(|om| listed(om.status, &lookup.statuses)) {
+            for rd in self.requirements(om.ordnum).filter(|rd| is_print_part(rd.prtnum)) {
+                for (ps, pm) in self.components(om.prtnum) {
+                    for wn in self.windows_notes.iter().filter(|wn| wn.comprt == rd.prtnum) {
+                        let subassembly = pm.part_type == "S" && prefixed(pm.prtnum, &lookup.subassembly_prefixes) && wn.prtnum == ps.comprt;
+                        let required = rd.prtnum == ps.comprt && wn.prtnum == om.prtnum;
+                        if subassembly || required {
+                            rows.push((wn.maxid, PrintItemRow {
+                                order_number: om.ordnum.to_string(),
+                                part_number: om.prtnum.to_string(),
+                                due_quantity: om.dueqty,
+                                assn_number: if pm.part_type == "S" { ps.comprt } else { om.prtnum }.to_string(),
+                                print_type: rd.prtnum.to_string(),
+                                notes: wn.notes.map(|s| s.to_string()),
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+        rows.sort_by_key(|(maxid, _)| *maxid);
+        let rows = rows.into_iter().map(|(_, row)| row).collect();
+        Box::pin(async move { Ok(rows) })
+    }
+
+    fn all_notes(&self) -> BoxFuture<'_, Result<Vec<NoteRow>, AppError>> {
+        let mut notes: Vec<&WindowsNote> = self.windows_notes.iter().filter(|wn| is_print_part(wn.comprt)).collect();
+        notes.sort_by_key(|wn| (wn.prtnum, wn.maxid));
+        let rows = notes
+            .into_iter()
+            .map(|wn| NoteRow {
+                part_number: wn.prtnum.to_string(),
+                print_type: wn.comprt.to_string(),
+                notes: wn.notes.map(|s| s.to_string()),
+            })
+            .collect();
+        Box::pin(async move { Ok(rows) })
+    }
+
+    fn common_parts(&self, order_number: String) -> BoxFuture<'_, Result<Vec<String>, AppError>> {
+        let mut common_parts = Vec::new();
+        for om in self.lookup(&order_number).filter(|om| self.part(om.prtnum).is_some()) {
+            for (_, pm2) in self.components(om.prtnum) {
+                for (_, pm3) in self.components(pm2.prtnum) {
+                    if is_common(pm2) || is_common(pm3) {
+                        common_parts.push(if is_common(pm3) { pm3.prtnum } else { pm2.prtnum }.to_string());
+                    }
+                }
+            }
+        }
+        Box::pin(async move { Ok(common_parts) })
+    }
+}
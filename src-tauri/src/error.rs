@@ -0,0 +1,104 @@
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+// broad area a failure came from, the frontend picks its remediation text from this
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    NotFound,
+    Printer,
+    Database,
+    Serial,
+    Config,
+    Report,
+    Io,
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct ErrorContext {
+    pub path: Option<String>,
+    pub printer: Option<String>,
+    pub order: Option<String>,
+}
+
+// error returned by every tauri command, serialized as { category, code, message, context }
+#[derive(Serialize, Debug, Clone)]
+pub struct AppError {
+    pub category: ErrorCategory,
+    pub code: &'static str,
+    pub message: String,
+    pub context: ErrorContext,
+}
+
+impl AppError {
+    pub fn new(category: ErrorCategory, code: &'static str, message: impl Into<String>) -> Self {
+        AppError {
+            category,
+            code,
+            message: message.into(),
+            context: ErrorContext::default(),
+        }
+    }
+
+    pub fn not_found(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::NotFound, code, message)
+    }
+
+    pub fn printer(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Printer, code, message)
+    }
+
+    pub fn database(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Database, code, message)
+    }
+
+    pub fn serial(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Serial, code, message)
+    }
+
+    pub fn config(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Config, code, message)
+    }
+
+    pub fn report(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Report, code, message)
+    }
+
+    pub fn io(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Io, code, message)
+    }
+
+    pub fn with_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.context.path = Some(path.as_ref().display().to_string());
+        self
+    }
+
+    pub fn with_printer(mut self, printer: impl Into<String>) -> Self {
+        self.context.printer = Some(printer.into());
+        self
+    }
+
+    pub fn with_order(mut self, order: impl Into<String>) -> Self {
+        self.context.order = Some(order.into());
+        self
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)?;
+        if let Some(path) = &self.context.path {
+            write!(f, " (path: {})", path)?;
+        }
+        if let Some(printer) = &self.context.printer {
+            write!(f, " (printer: {})", printer)?;
+        }
+        if let Some(order) = &self.context.order {
+            write!(f, " (order: {})", order)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AppError {}
@@ -0,0 +1,169 @@
+use chrono::NaiveDate;
+use crate::check_digit::CheckDigit;
+use crate::structs::Order;
+use crate::error::AppError;
+
+// GS1-128 / DataMatrix content for UDI labels. a label or Final DOCS note asks for it with a parameter naming the
+// part's GTIN, which print swaps for the element string of each unit:
+//   GS1:00812345678901     (01)00812345678901(11)250905(10)5000001(21)001360555, for barcode fonts that take
+//                          the AIs in brackets, and the text under the barcode
+//   GS1RAW:00812345678901  the same elements with a GS character (FNC1) after variable length fields, for encoders
+//                          that take the raw data
+const HRI_PREFIX: &str = "GS1:";
+const RAW_PREFIX: &str = "GS1RAW:";
+const GROUP_SEPARATOR: char = '\u{1d}';
+
+// the Application Identifiers used, fixed length ones first so only the lot needs a separator
+const GTIN: &str = "01";
+const PRODUCTION_DATE: &str = "11";
+const LOT: &str = "10";
+const SERIAL: &str = "21";
+const MAX_VARIABLE_LENGTH: usize = 20;
+
+pub struct ElementString {
+    elements: Vec<(&'static str, String)>,
+}
+
+impl ElementString {
+    // one unit of the order: the lot is the order number, the production date the day it prints and the serial as
+    // printed, check character included
+    pub fn for_unit(gtin: &str, order: &Order, serial: &str, production_date: NaiveDate) -> Result<Self, AppError> {
+        Ok(ElementString {
+            elements: vec![
+                (GTIN, gtin14(gtin)?),
+                (PRODUCTION_DATE, production_date.format("%y%m%d").to_string()),
+                (LOT, variable_field("lot", order.order_number.trim())?),
+                (SERIAL, variable_field("serial", serial)?),
+            ],
+        })
+    }
+
+    pub fn hri(&self) -> String {
+        self.elements.iter().map(|(ai, value)| format!("({}){}", ai, value)).collect()
+    }
+
+    pub fn data(&self) -> String {
+        let mut data = String::new();
+        for (i, (ai, value)) in self.elements.iter().enumerate() {
+            data.push_str(ai);
+            data.push_str(value);
+            if is_variable(ai) && i + 1 < self.elements.len() {
+                data.push(GROUP_SEPARATOR);
+            }
+        }
+        data
+    }
+}
+
+// GTIN-8, 12, 13 or 14 with a correct check digit, padded to the 14 digits AI 01 takes
+pub fn gtin14(gtin: &str) -> Result<String, AppError> {
+    let gtin = gtin.trim();
+    if !matches!(gtin.len(), 8 | 12 | 13 | 14) || !gtin.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(AppError::config("gs1_invalid", format!("GTIN '{}' should be 8, 12, 13 or 14 digits", gtin)));
+    }
+    let (body, check) = gtin.split_at(gtin.len() - 1);
+    if CheckDigit::Mod10.compute(body).map(String::from).as_deref() != Some(check) {
+        return Err(AppError::config("gs1_invalid", format!("GTIN '{}' has the wrong check digit", gtin)));
+    }
+    Ok(format!("{:0>14}", gtin))
+}
+
+// checks a GS1 note parameter when notes are parsed, so a bad GTIN shows up in the note lint rather than at the printer
+pub fn check_param(param: &str) -> Result<(), AppError> {
+    match gtin_param(param) {
+        Some((gtin, _)) => gtin14(gtin).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+// the note parameters for one unit, GS1 parameters swapped for its element string and the rest as they are
+pub fn expand_params(params: &[String], order: &Order, serial: &str, today: NaiveDate) -> Result<Vec<String>, AppError> {
+    params
+        .iter()
+        .map(|param| match gtin_param(param) {
+            Some((gtin, raw)) => {
+                let element_string = ElementString::for_unit(gtin, order, serial, today)
+                    .map_err(|e| e.with_order(&order.order_number))?;
+                Ok(if raw { element_string.data() } else { element_string.hri() })
+            }
+            None => Ok(param.clone()),
+        })
+        .collect()
+}
+
+// the GTIN of a GS1 parameter and whether it asks for raw data
+fn gtin_param(param: &str) -> Option<(&str, bool)> {
+    let param = param.trim();
+    if let Some(gtin) = param.strip_prefix(RAW_PREFIX) {
+        Some((gtin, true))
+    } else {
+        param.strip_prefix(HRI_PREFIX).map(|gtin| (gtin, false))
+    }
+}
+
+fn is_variable(ai: &str) -> bool {
+    ai == LOT || ai == SERIAL
+}
+
+// lots and serials are up to 20 characters from GS1's character set 82, less the double quote that would end the
+// quoted "ParmN:..." argument Visual CUT gets
+fn variable_field(name: &str, value: &str) -> Result<String, AppError> {
+    let allowed = |c: char| c.is_ascii_alphanumeric() || "!%&'()*+,-./:;<=>?_".contains(c);
+    if value.is_empty() || value.chars().count() > MAX_VARIABLE_LENGTH || !value.chars().all(allowed) {
+        return Err(AppError::config("gs1_invalid", format!("GS1 {} '{}' should be 1 to {} letters, digits or GS1 punctuation", name, value, MAX_VARIABLE_LENGTH)));
+    }
+    Ok(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::order;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn gtins_are_checked_and_padded() {
+        assert_eq!(gtin14("00812345678901").unwrap(), "00812345678901");
+        assert_eq!(gtin14("6291041500213").unwrap(), "06291041500213");
+        assert_eq!(gtin14(" 96385074 ").unwrap(), "00000096385074");
+        for gtin in ["00812345678904", "629104150021", "0081234567890A", ""] {
+            assert_eq!(gtin14(gtin).unwrap_err().code, "gs1_invalid", "{:?}", gtin);
+        }
+    }
+
+    #[test]
+    fn element_string_for_a_unit() {
+        let due = Order { due_date: Some(date(2025, 9, 5)), ..order(10.0) };
+        let unit = ElementString::for_unit("00812345678901", &due, "001360555", date(2025, 9, 3)).unwrap();
+
+        // the production date is the print date, never the order's due date
+        assert_eq!(unit.hri(), "(01)00812345678901(11)250903(10)5000001(21)001360555");
+        assert_eq!(unit.data(), "010081234567890111250903105000001\u{1d}21001360555");
+    }
+
+    #[test]
+    fn quotes_are_not_allowed_in_variable_fields() {
+        let mut quoted = order(10.0);
+        quoted.order_number = "5000\"01".to_string();
+
+        assert_eq!(ElementString::for_unit("00812345678901", &quoted, "001360555", date(2025, 9, 3)).err().unwrap().code, "gs1_invalid");
+    }
+
+    #[test]
+    fn params_swap_gs1_tokens() {
+        let params = vec!["Rev B".to_string(), "GS1:00812345678901".to_string(), "GS1RAW:00812345678901".to_string()];
+        let expanded = expand_params(&params, &order(10.0), "0013605555", date(2025, 9, 3)).unwrap();
+
+        assert_eq!(expanded[0], "Rev B");
+        assert_eq!(expanded[1], "(01)00812345678901(11)250903(10)5000001(21)0013605555");
+        assert!(expanded[2].ends_with("\u{1d}210013605555"));
+
+        assert_eq!(check_param("GS1:00812345678904").unwrap_err().code, "gs1_invalid");
+        assert!(check_param("Lot 7").is_ok());
+        // a mod 43 check character can be a space, which GS1 doesn't allow
+        assert_eq!(expand_params(&params, &order(10.0), "999999999 ", date(2025, 9, 3)).unwrap_err().code, "gs1_invalid");
+    }
+}
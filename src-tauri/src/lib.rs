@@ -1,24 +1,72 @@
 mod settings;
 mod sql;
 mod structs;
+mod serial_number;
+mod check_digit;
+mod gs1;
 mod serial_number_files;
 mod print;
+mod print_progress;
+mod printer_health;
+mod logging;
+mod error;
+mod doc_index;
+mod notes;
+mod cli;
+mod context;
+mod query_config;
+#[cfg(test)]
+mod erp_fixture;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            if let Err(e) = logging::init(app.handle()) {
+                eprintln!("Failed to start logging: {}", e);
+            }
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Ok(ctx) = context::AppContext::from_app(&handle) {
+                    print::clear_previews(&ctx);
+                }
+                doc_index::warm(&handle);
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             sql::get_order_number_info,
             sql::get_print_items,
             sql::get_orders,
             print::print,
             print::check_printer_regex,
+            print::preview_label,
+            print::open_order_archive,
+            printer_health::check_print_health,
+            printer_health::printer_dashboard,
+            print_progress::get_print_progress,
             serial_number_files::get_serial_number,
             serial_number_files::reset_serial_check,
             settings::save_settings,
             settings::load_settings,
+            settings::export_settings,
+            settings::import_settings,
+            settings::list_profiles,
+            settings::save_profile,
+            settings::switch_profile,
+            settings::delete_profile,
+            settings::resolve_path,
+            logging::get_recent_logs,
+            doc_index::rebuild_document_index,
+            notes::lint_order_notes,
+            notes::lint_all_notes,
             ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
-}
\ No newline at end of file
+}
+
+// entry point for the orderscanningpal-cli binary
+pub fn run_cli() -> std::process::ExitCode {
+    cli::run()
+}
@@ -0,0 +1,179 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{prelude::*, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::Local;
+use tauri::AppHandle;
+use crate::settings;
+use crate::context::AppContext;
+use crate::error::AppError;
+
+const LOG_DIR: &str = "logs";
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+const MAX_LOG_FILES: usize = 5;
+
+#[derive(Serialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+struct FileLogger {
+    dir: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // one entry per line so get_recent_logs can read them back
+        let message = record.args().to_string().replace(['\r', '\n'], " ");
+        let line = format!("{} [{}] {}: {}\n", Local::now().format("%Y-%m-%d %H:%M:%S"), record.level(), record.target(), message);
+
+        // dev builds still have a console, keep printing there
+        if cfg!(debug_assertions) {
+            eprint!("{}", line);
+        }
+
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = self.write_line(&mut file, &line) {
+            if cfg!(debug_assertions) {
+                eprintln!("Failed to write log file: {}", e);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(f) = file.as_mut() {
+            let _ = f.flush();
+        }
+    }
+}
+
+impl FileLogger {
+    fn write_line(&self, file: &mut Option<File>, line: &str) -> std::io::Result<()> {
+        if let Some(f) = file.as_ref() {
+            if f.metadata()?.len() + line.len() as u64 > MAX_LOG_BYTES {
+                // close the handle first, windows won't rename an open file
+                *file = None;
+                rotate(&self.dir)?;
+            }
+        }
+        if file.is_none() {
+            *file = Some(OpenOptions::new().create(true).append(true).open(log_file_path(&self.dir, 0))?);
+        }
+        match file.as_mut() {
+            Some(f) => f.write_all(line.as_bytes()),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn init(app_handle: &AppHandle) -> Result<(), AppError> {
+    init_in(&AppContext::from_app(app_handle)?)
+}
+
+// the app and the cli log to the same files in the data folder
+pub fn init_in(ctx: &AppContext) -> Result<(), AppError> {
+    let dir = ctx.file(LOG_DIR);
+    fs::create_dir_all(&dir).map_err(|e| AppError::io("log_dir", format!("Failed to create log directory: {}", e)).with_path(&dir))?;
+
+    let level = ctx.settings()
+        .map(|s| s.log_level)
+        .unwrap_or(LevelFilter::Info);
+
+    log::set_boxed_logger(Box::new(FileLogger { dir, file: Mutex::new(None) }))
+        .map_err(|e| AppError::config("logger", format!("Failed to set logger: {}", e)))?;
+    log::set_max_level(level);
+
+    // panics would otherwise vanish with the windows subsystem, the cli still prints them
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log::error!(target: "panic", "{}", info);
+        default_hook(info);
+    }));
+
+    Ok(())
+}
+
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+#[tauri::command]
+pub fn get_recent_logs(limit: usize, min_level: Option<String>, app_handle: AppHandle) -> Result<Vec<LogEntry>, AppError> {
+    let dir = settings::doc_file_path(LOG_DIR, &app_handle)?;
+    let min_level = match min_level {
+        Some(level) => level.parse::<Level>().map_err(|_| AppError::config("log_level", format!("Unknown log level '{}'", level)))?,
+        None => Level::Trace,
+    };
+
+    let mut entries = Vec::new();
+    // oldest rotated file first so the newest entries end up last
+    for index in (0..MAX_LOG_FILES).rev() {
+        let path = log_file_path(&dir, index);
+        if !path.exists() {
+            continue;
+        }
+        let file = File::open(&path).map_err(|e| AppError::io("log_read", format!("Failed to open log file: {}", e)).with_path(&path))?;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| AppError::io("log_read", format!("Failed to read log file: {}", e)).with_path(&path))?;
+            if let Some(entry) = parse_line(&line) {
+                if entry.level.parse::<Level>().is_ok_and(|l| l <= min_level) {
+                    entries.push(entry);
+                }
+            }
+        }
+    }
+
+    let skip = entries.len().saturating_sub(limit);
+    Ok(entries.into_iter().skip(skip).collect())
+}
+
+fn log_file_path(dir: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        dir.join("orderscanningpal.log")
+    } else {
+        dir.join(format!("orderscanningpal.{}.log", index))
+    }
+}
+
+fn rotate(dir: &Path) -> std::io::Result<()> {
+    let oldest = log_file_path(dir, MAX_LOG_FILES - 1);
+    if oldest.exists() {
+        fs::remove_file(oldest)?;
+    }
+    for index in (1..MAX_LOG_FILES).rev() {
+        let from = log_file_path(dir, index - 1);
+        if from.exists() {
+            fs::rename(from, log_file_path(dir, index))?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_line(line: &str) -> Option<LogEntry> {
+    // 2025-09-02 14:03:11 [ERROR] orderscanningpal_lib::print: message
+    let timestamp = line.get(..19)?;
+    let rest = line.get(19..)?.strip_prefix(" [")?;
+    let (level, rest) = rest.split_once("] ")?;
+    let (target, message) = rest.split_once(": ")?;
+
+    Some(LogEntry {
+        timestamp: timestamp.to_string(),
+        level: level.to_string(),
+        target: target.to_string(),
+        message: message.to_string(),
+    })
+}
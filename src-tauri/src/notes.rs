@@ -0,0 +1,423 @@
+use serde::Serialize;
+use tauri::AppHandle;
+use crate::settings::Settings;
+use crate::context::AppContext;
+use crate::doc_index;
+use crate::gs1;
+use crate::sql;
+use crate::error::AppError;
+
+// Windows_Notes.NOTES_61 is a '?' separated list, what each position means depends on the component
+//   94A / K94A labels:  report?parm3?parm4?parm5
+//   Initial DOCS:       folder?document?printer
+//   Final DOCS:         folder?report?parm3?parm4?parm5
+// a label or Final DOCS parameter of GS1:<gtin> is filled in per unit, see gs1.rs
+// a note starting with '~' is switched off, one starting with '?' carries on the note above it
+const SEPARATOR: char = '?';
+const SKIP_MARK: char = '~';
+const MAX_PARAMS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteKind {
+    Label,
+    InitialDocs,
+    FinalDocs,
+}
+
+impl NoteKind {
+    // None for the rows that don't come from a note (BOM, Config, SNL)
+    pub fn of(print_type: &str) -> Option<Self> {
+        let print_type = print_type.trim();
+        if print_type.starts_with("94A") || print_type.starts_with("K94A") {
+            Some(NoteKind::Label)
+        } else if print_type.eq_ignore_ascii_case("initial docs") {
+            Some(NoteKind::InitialDocs)
+        } else if print_type.eq_ignore_ascii_case("final docs") {
+            Some(NoteKind::FinalDocs)
+        } else {
+            None
+        }
+    }
+}
+
+pub enum NoteLine<'a> {
+    Empty,
+    Skip,
+    Continuation(&'a str),
+    Start(&'a str),
+}
+
+pub fn classify(notes: Option<&str>) -> NoteLine<'_> {
+    match notes {
+        None => NoteLine::Empty,
+        Some(n) if n.trim().is_empty() => NoteLine::Empty,
+        Some(n) if n.starts_with(SKIP_MARK) => NoteLine::Skip,
+        Some(n) if n.starts_with(SEPARATOR) => NoteLine::Continuation(n),
+        Some(n) => NoteLine::Start(n),
+    }
+}
+
+// notes as print reads them: empty and switched off notes dropped, a continuation line added to the note above it.
+// a continuation with no note above it goes to orphan
+pub fn join_notes<'a, T>(rows: impl IntoIterator<Item = (T, Option<&'a str>)>, mut orphan: impl FnMut(T, &'a str)) -> Vec<(T, String)> {
+    let mut joined: Vec<(T, String)> = Vec::new();
+    for (row, notes) in rows {
+        match classify(notes) {
+            NoteLine::Empty | NoteLine::Skip => continue,
+            NoteLine::Continuation(more) => match joined.last_mut() {
+                Some((_, above)) => above.push_str(more),
+                None => orphan(row, more),
+            },
+            NoteLine::Start(note) => joined.push((row, note.to_string())),
+        }
+    }
+    joined
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "name", rename_all = "snake_case")]
+pub enum PrinterHint {
+    Default,
+    Clr,
+    Named(String),
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Instruction {
+    Label { report: String, params: Vec<String> },
+    InitialDocs { search_path: String, document: String, printer: PrinterHint },
+    FinalDocs { search_path: String, report: String, params: Vec<String> },
+}
+
+#[derive(Serialize)]
+pub struct NoteLint {
+    pub print_type: String,
+    pub notes: String,
+    pub instruction: Option<Instruction>,
+    pub error: Option<AppError>,
+}
+
+#[derive(Serialize)]
+pub struct NoteProblem {
+    pub part_number: String,
+    pub print_type: String,
+    pub notes: String,
+    pub problem: AppError,
+}
+
+#[derive(Serialize)]
+pub struct NotesReport {
+    pub checked: usize,
+    pub problems: Vec<NoteProblem>,
+}
+
+// a note with its continuation lines joined, as get_print_items would hand it to print
+struct JoinedNote {
+    part_number: String,
+    print_type: String,
+    notes: String,
+}
+
+pub fn parse(print_type: &str, notes: &str) -> Result<Instruction, AppError> {
+    let kind = NoteKind::of(print_type)
+        .ok_or_else(|| AppError::config("note_kind_unknown", format!("{} rows are not driven by a note", print_type.trim())))?;
+
+    let mut segments: Vec<&str> = notes.split(SEPARATOR).map(str::trim).collect();
+    // a stray '?' at the end (often left by a continuation line) is not a parameter
+    while segments.len() > 1 && segments.last() == Some(&"") {
+        segments.pop();
+    }
+
+    let required = |index: usize, name: &str| -> Result<String, AppError> {
+        match segments.get(index) {
+            Some(s) if !s.is_empty() => Ok(s.to_string()),
+            Some(_) => Err(malformed(print_type, notes, format!("segment {} ({}) is empty", index + 1, name))),
+            None => Err(malformed(print_type, notes, format!("segment {} ({}) is missing", index + 1, name))),
+        }
+    };
+    let params = |from: usize| -> Result<Vec<String>, AppError> {
+        let params: Vec<String> = segments.iter().skip(from).map(|s| s.to_string()).collect();
+        if params.len() > MAX_PARAMS {
+            return Err(malformed(print_type, notes, format!(
+                "has {} parameters after segment {}, only {} can be passed to the report",
+                params.len(), from, MAX_PARAMS)));
+        }
+        for param in &params {
+            gs1::check_param(param).map_err(|e| malformed(print_type, notes, e.message))?;
+        }
+        Ok(params)
+    };
+
+    match kind {
+        NoteKind::Label => Ok(Instruction::Label {
+            report: required(0, "report name")?,
+            params: params(1)?,
+        }),
+        NoteKind::InitialDocs => {
+            if segments.len() > 3 {
+                return Err(malformed(print_type, notes, format!("has {} segments, expected folder?document?printer", segments.len())));
+            }
+            let printer = match segments.get(2) {
+                None | Some(&"") => PrinterHint::Default,
+                Some(p) if p.eq_ignore_ascii_case("clr") => PrinterHint::Clr,
+                Some(p) => PrinterHint::Named(p.to_string()),
+            };
+            Ok(Instruction::InitialDocs {
+                search_path: required(0, "folder")?,
+                document: required(1, "document name")?,
+                printer,
+            })
+        }
+        NoteKind::FinalDocs => Ok(Instruction::FinalDocs {
+            search_path: required(0, "folder")?,
+            report: required(1, "report name")?,
+            params: params(2)?,
+        }),
+    }
+}
+
+fn malformed(print_type: &str, notes: &str, problem: String) -> AppError {
+    AppError::config("note_malformed", format!("{} note '{}' {}", print_type.trim(), notes, problem))
+}
+
+// checks every label and docs note in the ERP the way get_print_items and print would read it
+pub async fn lint_all(ctx: &AppContext) -> Result<NotesReport, AppError> {
+    let app_settings = ctx.settings()?.with_resolved_paths();
+    let mut rows = ctx.erp().all_notes().await?;
+    rows.retain(|row| !app_settings.part_list.contains(&row.print_type));
+    let mut joined: Vec<JoinedNote> = Vec::new();
+    let mut problems = Vec::new();
+
+    // a part's notes print together, after the BOM, Config and SNL rows
+    for part in rows.chunk_by(|a, b| a.part_number == b.part_number) {
+        let notes = join_notes(part.iter().map(|row| (row, row.notes.as_deref())), |row, more| problems.push(NoteProblem {
+            problem: AppError::config("note_orphan_continuation", format!("{} note '{}' continues a note that isn't there", row.print_type, more)),
+            part_number: row.part_number.clone(),
+            print_type: row.print_type.clone(),
+            notes: more.to_string(),
+        }));
+        joined.extend(notes.into_iter().map(|(row, notes)| JoinedNote {
+            part_number: row.part_number.clone(),
+            print_type: row.print_type.clone(),
+            notes,
+        }));
+    }
+
+    let checked = joined.len();
+    for note in joined {
+        for problem in check_note(&note, &app_settings) {
+            problems.push(NoteProblem {
+                part_number: note.part_number.clone(),
+                print_type: note.print_type.clone(),
+                notes: note.notes.clone(),
+                problem,
+            });
+        }
+    }
+
+    Ok(NotesReport { checked, problems })
+}
+
+fn check_note(note: &JoinedNote, app_settings: &Settings) -> Vec<AppError> {
+    let mut problems = Vec::new();
+    let (root, report) = match parse(&note.print_type, &note.notes) {
+        Err(e) => return vec![e],
+        Ok(Instruction::Label { report, .. }) => {
+            if app_settings.label_printer(&note.print_type).is_none() {
+                problems.push(AppError::printer("label_printer_unmapped", format!("No label printer is set up for {}", note.print_type)));
+            }
+            (app_settings.label_path.clone(), report)
+        }
+        Ok(Instruction::InitialDocs { search_path, document, .. }) => (app_settings.resolve_path(&search_path), document),
+        Ok(Instruction::FinalDocs { search_path, report, .. }) => (app_settings.resolve_path(&search_path), report),
+    };
+
+    match doc_index::find_document(&root, &report) {
+        Ok(Some(_)) => (),
+        Ok(None) => problems.push(AppError::not_found("document_missing", format!("No document matching '{}'", report)).with_path(&root)),
+        Err(e) => problems.push(e),
+    }
+    problems
+}
+
+#[tauri::command]
+pub async fn lint_all_notes(app_handle: AppHandle) -> Result<NotesReport, AppError> {
+    lint_all(&AppContext::from_app(&app_handle)?).await
+}
+
+#[tauri::command]
+pub async fn lint_order_notes(order_number: String, app_handle: AppHandle) -> Result<Vec<NoteLint>, AppError> {
+    let ctx = AppContext::from_app(&app_handle)?;
+    let rows = sql::print_items(order_number.clone(), &ctx.settings()?, &ctx.query_config()?, ctx.erp()).await?;
+
+    Ok(rows
+        .into_iter()
+        .filter(|row| NoteKind::of(&row.print_type).is_some())
+        .map(|row| {
+            let (instruction, error) = match parse(&row.print_type, &row.notes) {
+                Ok(instruction) => (Some(instruction), None),
+                Err(e) => (None, Some(e.with_order(&order_number))),
+            };
+            NoteLint { print_type: row.print_type, notes: row.notes, instruction, error }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+    use crate::context::testing::temp_context_with;
+    use crate::erp_fixture::{FixtureErp, WindowsNote};
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn label_notes_are_a_report_and_parameters() {
+        let cases = [
+            ("94A000003", "ProductLabel?Rev B?Lot 7", "ProductLabel", strings(&["Rev B", "Lot 7"])),
+            ("K94A000005", "RackLabel?K", "RackLabel", strings(&["K"])),
+            ("94A000004-A01", "BoxLabel", "BoxLabel", vec![]),
+            ("94A000003", "01A000038-A01?GS1:09506000134352", "01A000038-A01", strings(&["GS1:09506000134352"])),
+            // an empty parameter in the middle still holds its place
+            ("94A000003", "ProductLabel??Lot 7", "ProductLabel", strings(&["", "Lot 7"])),
+        ];
+
+        for (print_type, notes, report, params) in cases {
+            assert_eq!(parse(print_type, notes).unwrap(), Instruction::Label { report: report.to_string(), params }, "{}", notes);
+        }
+    }
+
+    #[test]
+    fn initial_docs_name_the_printer() {
+        let cases = [
+            ("P:\\Work Instructions?WI-100?clr", PrinterHint::Clr),
+            ("P:\\Work Instructions?WI-100?CLR", PrinterHint::Clr),
+            ("P:\\Work Instructions?WI-100?HP Shop Floor", PrinterHint::Named("HP Shop Floor".to_string())),
+            ("P:\\Work Instructions?WI-100", PrinterHint::Default),
+            ("P:\\Work Instructions?WI-100?", PrinterHint::Default),
+        ];
+
+        for (notes, printer) in cases {
+            let expected = Instruction::InitialDocs {
+                search_path: "P:\\Work Instructions".to_string(),
+                document: "WI-100".to_string(),
+                printer,
+            };
+            assert_eq!(parse("Initial DOCS", notes).unwrap(), expected, "{}", notes);
+        }
+    }
+
+    #[test]
+    fn final_docs_are_a_folder_report_and_parameters() {
+        assert_eq!(parse("Final DOCS", "P:\\Final?FinalReport?100").unwrap(), Instruction::FinalDocs {
+            search_path: "P:\\Final".to_string(),
+            report: "FinalReport".to_string(),
+            params: strings(&["100"]),
+        });
+        assert_eq!(parse("final docs", "P:\\Final?01A000207-A01").unwrap(), Instruction::FinalDocs {
+            search_path: "P:\\Final".to_string(),
+            report: "01A000207-A01".to_string(),
+            params: vec![],
+        });
+    }
+
+    #[test]
+    fn older_notes_are_still_read() {
+        // written before the parser trimmed and before continuation lines were joined
+        let cases = [
+            ("94A000003", "ProductLabel?Rev B?", Instruction::Label { report: "ProductLabel".to_string(), params: strings(&["Rev B"]) }),
+            ("94A000003", " ProductLabel ? Rev B ", Instruction::Label { report: "ProductLabel".to_string(), params: strings(&["Rev B"]) }),
+            ("94A000003", "ProductLabel?Rev B?Lot 7??", Instruction::Label { report: "ProductLabel".to_string(), params: strings(&["Rev B", "Lot 7"]) }),
+            ("Final DOCS", "P:\\Final?FinalReport?", Instruction::FinalDocs { search_path: "P:\\Final".to_string(), report: "FinalReport".to_string(), params: vec![] }),
+        ];
+
+        for (print_type, notes, expected) in cases {
+            assert_eq!(parse(print_type, notes).unwrap(), expected, "{}", notes);
+        }
+    }
+
+    #[test]
+    fn malformed_notes_are_rejected() {
+        let cases = [
+            ("94A000003", "?Rev B"),
+            ("94A000003", "ProductLabel?a?b?c?d"),
+            ("94A000003", "ProductLabel?GS1:09506000134353"),
+            ("Initial DOCS", "P:\\Work Instructions"),
+            ("Initial DOCS", "P:\\Work Instructions?WI-100?clr?extra"),
+            ("Final DOCS", "?FinalReport"),
+            ("Final DOCS", "P:\\Final?FinalReport?a?b?c?d"),
+        ];
+
+        for (print_type, notes) in cases {
+            assert_eq!(parse(print_type, notes).unwrap_err().code, "note_malformed", "{}", notes);
+        }
+        assert_eq!(parse("BOM", "anything").unwrap_err().code, "note_kind_unknown");
+    }
+
+    #[test]
+    fn continuations_join_the_note_above() {
+        let rows = [
+            ("Initial DOCS", Some("P:\\Work Instructions?WI-100?clr")),
+            ("94A000003", Some("ProductLabel?Rev B")),
+            ("94A000003", Some("~OldLabel")),
+            ("94A000003", Some("?Lot 7")),
+            ("94A000003", None),
+            ("94A000004-A01", Some("BoxLabel")),
+            ("Final DOCS", Some("?100")),
+        ];
+        let mut orphans = Vec::new();
+
+        let joined = join_notes(rows, |print_type, more| orphans.push((print_type, more)));
+
+        // a switched off line in between doesn't break the note, and a continuation carries on whichever note is above
+        assert_eq!(joined, vec![
+            ("Initial DOCS", "P:\\Work Instructions?WI-100?clr".to_string()),
+            ("94A000003", "ProductLabel?Rev B?Lot 7".to_string()),
+            ("94A000004-A01", "BoxLabel?100".to_string()),
+        ]);
+        assert!(orphans.is_empty());
+    }
+
+    #[test]
+    fn continuations_with_nothing_above_are_orphans() {
+        let rows = [("94A000003", Some("?Lot 7")), ("94A000003", Some("   ")), ("94A000004-A01", Some("BoxLabel"))];
+        let mut orphans = Vec::new();
+
+        let joined = join_notes(rows, |print_type, more| orphans.push((print_type, more)));
+
+        assert_eq!(joined, vec![("94A000004-A01", "BoxLabel".to_string())]);
+        assert_eq!(orphans, vec![("94A000003", "?Lot 7")]);
+    }
+
+    #[tokio::test]
+    async fn lint_joins_notes_per_part_the_way_print_does() {
+        let mut erp = FixtureErp::seeded();
+        // first note of the rack's notes, so there is nothing above it to carry on
+        erp.windows_notes.push(WindowsNote { maxid: 55, prtnum: "K02A000300", comprt: "K94A000005", notes: Some("?Orphan") });
+        let ctx = temp_context_with("lint-joins", Local::now(), erp);
+
+        let report = lint_all(&ctx).await.unwrap();
+
+        // Initial DOCS, ProductLabel?Rev B?Lot 7, BoxLabel and Final DOCS, the rack's two and the one on the rack itself
+        assert_eq!(report.checked, 7);
+        let orphans: Vec<(&str, &str)> = report.problems
+            .iter()
+            .filter(|p| p.problem.code == "note_orphan_continuation")
+            .map(|p| (p.part_number.as_str(), p.notes.as_str()))
+            .collect();
+        assert_eq!(orphans, vec![("K02A000300", "?Orphan")]);
+    }
+
+    #[test]
+    fn note_lines_are_classified_by_their_first_character() {
+        assert!(matches!(classify(None), NoteLine::Empty));
+        assert!(matches!(classify(Some("  ")), NoteLine::Empty));
+        assert!(matches!(classify(Some("~OldLabel")), NoteLine::Skip));
+        assert!(matches!(classify(Some("?Lot 7")), NoteLine::Continuation("?Lot 7")));
+        assert!(matches!(classify(Some("BoxLabel")), NoteLine::Start("BoxLabel")));
+    }
+}
@@ -1,36 +1,81 @@
-use walkdir::WalkDir;
 use std::process::{Command, ExitStatus};
 use std::os::windows::process::CommandExt;
-use std::path::PathBuf;
-use printers::{get_printer_by_name, get_printers};
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::ops::Range;
+use printers::{get_default_printer, get_printer_by_name, get_printers};
 use tauri::{AppHandle};
-use once_cell::sync::OnceCell;
-use crate::settings;
+use tauri::ipc::Response;
+use tauri_plugin_opener::OpenerExt;
+use log::{debug, error, info, trace, warn};
+use crate::context::AppContext;
+use crate::doc_index;
+use crate::gs1;
+use crate::notes::{self, Instruction, PrinterHint};
+use crate::print_progress;
+use crate::serial_number::SerialNumber;
 use crate::serial_number_files;
-use crate::sql;
-use crate::structs::{Order, PrintOrderRow};
-
-
-static DEFAULT_PRINTER: OnceCell<String> = OnceCell::new();
+use crate::structs::{Order, PrintBatch, PrintOrderRow, PrintOutcome};
+use crate::settings::Settings;
+use crate::error::AppError;
+
+const VC_EXE_PATH: &str = r"C:\Program Files (x86)\Visual CUT 11\Visual CUT.exe";
+const PREVIEW_DIR: &str = "previews";
+
+// prints a Word document on one printer through Word's COM interface. WordBasic's FilePrintSetup with
+// DoNotSetAsSysDefault points Word at the printer without touching the Windows default, which setting
+// ActivePrinter would change. the document and printer come in as environment variables so nothing needs quoting
+const WORD_PRINT_SCRIPT: &str = r#"
+$ErrorActionPreference = 'Stop'
+$failed = $false
+$word = New-Object -ComObject Word.Application
+try {
+    $word.Visible = $false
+    $word.DisplayAlerts = 0
+    $word.Options.PrintBackground = $false
+    $doc = $word.Documents.Open($env:PRINT_DOCUMENT, $false, $true)
+    [System.__ComObject].InvokeMember('FilePrintSetup', [System.Reflection.BindingFlags]::InvokeMethod, $null, $word.WordBasic, @($env:PRINT_PRINTER, 1), $null, $null, @('Printer', 'DoNotSetAsSysDefault')) | Out-Null
+    $doc.PrintOut() | Out-Null
+    $doc.Close(0)
+} catch {
+    [Console]::Error.WriteLine($_)
+    $failed = $true
+} finally {
+    $word.Quit(0)
+    [System.Runtime.InteropServices.Marshal]::ReleaseComObject($word) | Out-Null
+}
+if ($failed) { exit 1 }
+"#;
 
 #[tauri::command]
-pub async fn print(order: Order, print_order_row: PrintOrderRow, user: String, serial_number: String, reprint_run: bool, app_handle: AppHandle) -> Result<String, String> {
-    let vc_exe_path = r"C:\Program Files (x86)\Visual CUT 11\Visual CUT.exe";
+pub async fn print(order: Order, print_order_row: PrintOrderRow, user: String, serial_number: String, reprint_run: bool, batch: PrintBatch, app_handle: AppHandle) -> Result<PrintOutcome, AppError> {
+    print_row(order, print_order_row, user, SerialNumber::parse(&serial_number)?, reprint_run, batch, &AppContext::from_app(&app_handle)?).await
+}
+
+pub async fn print_row(order: Order, print_order_row: PrintOrderRow, user: String, serial_number: SerialNumber, reprint_run: bool, batch: PrintBatch, ctx: &AppContext) -> Result<PrintOutcome, AppError> {
+    let vc_exe_path = VC_EXE_PATH;
     let word_exe_path = r"C:\Program Files\Microsoft Office\root\Office16\WINWORD.EXE";
-    let printer_name;
+    let copies;
+    let mut archive_failures = Vec::new();
  
-    let app_settings = settings::internal_load_settings(&app_handle)?;          
-    
-    match get_default_printer_cached() {
-        Some(printer) => printer_name=printer.to_string(),
-        None => printer_name="Brother HL-2270DW series".to_string(),
-    }   
-    println!("Default Printer: {}", printer_name); 
+    let app_settings = ctx.settings()?.with_resolved_paths();
+
+    // labels and Final DOCS print one copy per unit, the batch picks which units of the order this run covers
+    let counts_units = print_order_row.print_type.to_lowercase() == "final docs" || print_order_row.print_type.starts_with("94A") || print_order_row.print_type.starts_with("K94A");
+    let units = if counts_units {
+        print_progress::plan(&order, &print_order_row, &batch, reprint_run, &app_settings, ctx)?
+    } else {
+        0..0
+    };
+    let quantity = units.len() as u32;
+    // checked before anything prints
+    let next_serial = serial_number.checked_add(quantity)?;
 
     //handle each type of print
     if print_order_row.print_type == "BOM" {
+        let printer_name = default_printer(&app_settings)?;
         // call to SQL to check for more
-        let common_parts = sql::common_parts(order.order_number).await.unwrap_or_default();
+        let common_parts = ctx.erp().common_parts(order.order_number.clone()).await.unwrap_or_default();
         let status: ExitStatus;
         let common_parts_str = common_parts
             .iter()
@@ -53,412 +98,583 @@ pub async fn print(order: Order, print_order_row: PrintOrderRow, user: String, s
                 )
             }
         };
-        status = Command::new(vc_exe_path)
-            .arg("-e")
-            .arg(app_settings.bom_path)
-            .raw_arg(&parm1_arg)
+        let bom_command = || {
+            let mut command = Command::new(vc_exe_path);
+            command.arg("-e").arg(&app_settings.bom_path).raw_arg(&parm1_arg);
+            command
+        };
+        status = bom_command()
             .raw_arg(&format!("\"Printer_Only:{}\"", printer_name))
             .status()
-            .map_err(|e| format!("Failed to execute process: {}", e))?;
-        println!("Process exited with status: {}", status);
+            .map_err(|e| launch_error(vc_exe_path, e))?;
+        info!("Process exited with status: {}", status);
+        check_status(status, &app_settings.bom_path, &printer_name, &order)?;
+        archive_failures = archive(vec![ArchiveCopy::new(bom_command(), &order, &print_order_row, &[])], &order, &app_settings).await;
+        copies = 1;
     } else if print_order_row.print_type == "Config" {
+        let printer_name = default_printer(&app_settings)?;
         // search for config path
-        match finder(&app_settings.config_path, order.part_number.clone()) {
-            Ok(v) =>  
-                for path in v {
-                    let status = Command::new(word_exe_path)
-                        .arg("-e")
-                        .arg(path.display().to_string())
-                        .arg("/q")
-                        .arg("/n")
-                        .arg("/mFilePrintDefault")
-                        .arg("/mFileCloseOrExit")
-                        .arg("/mFileExit")
-                        .status()
-                        .map_err(|e| format!("Failed to execute Word: {}", e))?;
-
-                    println!("Process exited with status: {}", status);
-                    break;
-                },
-            Err(e) => println!("error finding file: {e:?}")
-        }
+        let path = match finder(&app_settings.config_path, &order.part_number)? {
+            Some(path) => path,
+            None => return Ok(PrintOutcome::not_found(&app_settings.config_path, &order.part_number)),
+        };
+        let status = Command::new(word_exe_path)
+            .arg("-e")
+            .arg(path.display().to_string())
+            .arg("/q")
+            .arg("/n")
+            .arg("/mFilePrintDefault")
+            .arg("/mFileCloseOrExit")
+            .arg("/mFileExit")
+            .status()
+            .map_err(|e| launch_error(word_exe_path, e))?;
 
+        info!("Process exited with status: {}", status);
+        check_status(status, &path, &printer_name, &order)?;
+        copies = 1;
     } else if print_order_row.print_type == "SNL" {
-        let status = Command::new(vc_exe_path)
-            .arg("-e")
-            .arg(app_settings.snl_path)
-            .raw_arg(&format!("\"Parm1:{}\"", order.order_number))
+        let printer_name = default_printer(&app_settings)?;
+        let print_copies = match batch.quantity {
+            Some(quantity) => quantity,
+            None => order.units(app_settings.fractional_quantity)?,
+        };
+        let snl_command = || {
+            let mut command = Command::new(vc_exe_path);
+            command.arg("-e").arg(&app_settings.snl_path).raw_arg(&format!("\"Parm1:{}\"", order.order_number));
+            command
+        };
+        let status = snl_command()
             .raw_arg(&format!("\"Printer_Only:{}\"", printer_name))
-            .raw_arg(&format!("\"Print_Copies:{}\"", order.due_quantity))
+            .raw_arg(&format!("\"Print_Copies:{}\"", print_copies))
             .status()
-            .map_err(|e| format!("Failed to execute process: {}", e))?;
-        println!("Process exited with status: {}", status);
-        
+            .map_err(|e| launch_error(vc_exe_path, e))?;
+        info!("Process exited with status: {}", status);
+        check_status(status, &app_settings.snl_path, &printer_name, &order)?;
+        archive_failures = archive(vec![ArchiveCopy::new(snl_command(), &order, &print_order_row, &[])], &order, &app_settings).await;
+        copies = 1;
     } else if print_order_row.print_type.starts_with("94A") || print_order_row.print_type.starts_with("K94A") {
-        let parts = print_order_row.notes.split("?");
-        let collection: Vec<&str> = parts.collect();
-        let report_name = collection.get(0).unwrap().to_string();
-        let mut parm1 = collection.get(1);
-        let parm2 = collection.get(2);
-        let parm3 = collection.get(3);
-        
-        // forced to default value indicator $ (setup in crystal reports)
-        if report_name == "01A000038-A01" || report_name == "01A000039-A01" || report_name == "01A000052-A01" {
-            if parm1 == None {
-                parm1 = Some(&"$");
-            }
-        }
+        let Instruction::Label { report: report_name, mut params } = parse_notes(&print_order_row, &order)? else {
+            return Err(wrong_instruction(&print_order_row, &order, "a label"));
+        };
+
+        default_params(&report_name, &mut params);
 
         // match to correct printer
-        let printer_name;
-        if print_order_row.print_type == "94A000003-A01" {
-            printer_name =  &app_settings.label_printer_2_025;
-        } else if print_order_row.print_type == "94A000004-A01" {
-            printer_name = &app_settings.label_printer_075_025;
-        } else if print_order_row.print_type == "94A000005-A01" {
-            printer_name = &app_settings.label_printer_2_3;
-        } else if print_order_row.print_type == "94A000006-A01" {
-            printer_name = &app_settings.label_printer_125_025;
-        } else if print_order_row.print_type == "94A000047-A01" {
-            printer_name = &app_settings.label_printer_4_6;
-        } else {
-            let error = "Could not match label to a printer";
-            return Err(error.to_string());
-        }
+        let Some(printer_name) = app_settings.label_printer(&print_order_row.print_type) else {
+            return Err(AppError::printer("label_printer_unmapped", format!("Could not match label {} to a printer", print_order_row.print_type))
+                .with_order(&order.order_number));
+        };
         
         // get extension
-        match finder(&app_settings.label_path, report_name) {
-            Ok(v) =>
-                for path in v {
-                    for i in 0..(order.due_quantity as i32) {
-                        let snn = serial_number.parse::<i32>().unwrap() + i;
-                        let width = serial_number.len();
-                        let new_serial = format!("{:0width$}", snn, width = width);
-
-                        let mut command = Command::new(vc_exe_path);
-                        command.arg("-e");
-                        command.arg(path.display().to_string());
-
-                        // Parm arguments
-                        command.raw_arg(&format!("\"Parm1:{}\"", order.order_number));
-                        command.raw_arg(&format!("\"Parm2:{}\"", new_serial));
-
-                        if let Some(a) = &parm1 {
-                            command.raw_arg(&format!("\"Parm3:{}\"", a));
-                        }
-                        if let Some(a) = &parm2 {
-                            command.raw_arg(&format!("\"Parm4:{}\"", a));
-                        }
-                        if let Some(a) = &parm3 {
-                            command.raw_arg(&format!("\"Parm5:{}\"", a));
-                        }
-
-                        command.raw_arg(&format!("\"Printer_Only:{}\"", printer_name));
-
-                        let status = command
-                            .status()
-                            .map_err(|e| format!("Failed to execute process: {}", e))?;
-
-                        if !status.success() {
-                            return Err(format!("Process exited with non-zero status: {}", status));
-                        }
-
-                        println!("Printed serial: {} (exit: {})", new_serial, status);
-                    }
-                    break;
-                },
-            Err(e) => println!("error finding file: {e:?}")
-        }
+        let path = match finder(&app_settings.label_path, &report_name)? {
+            Some(path) => path,
+            None => return Ok(PrintOutcome::not_found(&app_settings.label_path, &report_name)),
+        };
+        for (unit, index) in units_to_print(&order, serial_number, quantity, &params, &app_settings, ctx)?.into_iter().zip(units.clone()) {
+            let mut command = unit_command(vc_exe_path, &path, &order, &unit);
+            command.raw_arg(&format!("\"Printer_Only:{}\"", printer_name));
 
-    } else if print_order_row.print_type.to_lowercase() == "initial docs" {
-        //parse notes
-        let parts = print_order_row.notes.split("?");
-        let collection: Vec<&str> = parts.collect();
-        let mut search_path = collection.get(0).unwrap().to_string();
-        let report_name = collection.get(1).unwrap().to_string();
-        let mut printer_desc = collection.get(2).map_or(printer_name.clone(), |v| v.to_string());
-        
-        // swap to clr printer
-        if printer_desc.to_lowercase() == "clr" {
-            printer_desc = app_settings.clr_printer;
-        }
+            let status = command
+                .status()
+                .map_err(|e| launch_error(vc_exe_path, e))?;
+            check_status(status, &path, printer_name, &order)?;
 
-        search_path = swap_drive(search_path);
+            info!("Printed serial: {} (exit: {})", unit.printed_serial, status);
+            record_printed(&order, &print_order_row, &user, unit.serial, index..index + 1, reprint_run, ctx).await?;
+        }
+        copies = quantity;
+    } else if print_order_row.print_type.to_lowercase() == "initial docs" {
+        let Instruction::InitialDocs { search_path, document: report_name, printer } = parse_notes(&print_order_row, &order)? else {
+            return Err(wrong_instruction(&print_order_row, &order, "an Initial DOCS"));
+        };
+        let search_path = app_settings.resolve_path(&search_path);
+        let printer_desc = match printer {
+            PrinterHint::Default => default_printer(&app_settings)?,
+            PrinterHint::Clr => app_settings.clr_printer.clone(),
+            PrinterHint::Named(name) => name,
+        };
 
         // serach for document
-        match finder(&search_path.as_str(), report_name) {
-            Ok(v) =>
-                for path in v {
-                    let extension = path.extension().unwrap();
-                    let status: ExitStatus;
-                    if extension == "pdf" { 
-                        status = Command::new(&app_settings.pdf_to_printer_path)
-                            .arg("/s")
-                            .arg(format!("{}", path.display()))
-                            .arg(format!("{}", printer_desc))
-                            .status()
-                            .map_err(|e| format!("Failed to execute process: {}", e))?;
-                    } else if extension == "docx" || extension == "doc" {
-                        let target_printer = printer_desc.as_str();
-                        match get_printer_by_name(target_printer) {
-                            Some(_) => {
-                                match set_default_printer(target_printer) {
-                                    Ok(_) => println!("Successfully set '{}' as default printer.", target_printer),
-                                    Err(e) => eprintln!("Failed to set default printer: {:?}", e),
-                                }
-                            }
-                            None => {
-                                println!("Printer '{}' not found.", target_printer);
-                                return Err(format!("Printer '{}' not found.", target_printer));
-                            }
-                        }
-
-                        status = Command::new(word_exe_path)
-                            .arg("-e")
-                            .arg(path.display().to_string())
-                            .arg("/q")
-                            .arg("/n")
-                            .arg("/mFilePrintDefault")
-                            .arg("/mFileCloseOrExit")
-                            .arg("/mFileExit")
-                            .status()
-                            .map_err(|e| format!("Failed to execute process: {}", e))?;
-                        
-                        match set_default_printer(printer_name.as_str()) {
-                            Ok(_) => println!("Successfully set '{}' as default printer.", printer_name),
-                            Err(e) => eprintln!("Failed to set default printer: {:?}", e),
-                        }
-
-                    } else if extension == "xlsx" || extension == "xls" { 
-                        status = Command::new("powershell")
-                            .arg("-Command")
-                            .arg(format!(
-                                "Start-Process -FilePath '{}' -Verb Print",
-                                path.display()
-                            ))
-                            .status()
-                            .map_err(|e| format!("Failed to execute process: {}", e))?;
-                        
-                    } else if extension == "jpg" || extension == "png" {
-                        status = Command::new("mspaint.exe")
-                            .arg("/p")
-                            .arg(path.display().to_string())
-                            .status()
-                            .map_err(|e| format!("Failed to execute process: {}", e))?;
-                    } else {
-                        status = Command::new("")
-                            .status()
-                            .map_err(|e| format!("Failed to execute process: {}", e))?;
-                    }
-
-                    println!("Process exited with status: {}", status);
-                    
-                    break;
-                },
-
-            Err(e) => println!("error finding file: {e:?}")
+        let path = match finder(&search_path, &report_name)? {
+            Some(path) => path,
+            None => return Ok(PrintOutcome::not_found(&search_path, &report_name)),
+        };
+        let extension = path.extension().unwrap_or_default();
+        let status: ExitStatus;
+        if extension == "pdf" { 
+            status = Command::new(&app_settings.pdf_to_printer_path)
+                .arg("/s")
+                .arg(format!("{}", path.display()))
+                .arg(format!("{}", printer_desc))
+                .status()
+                .map_err(|e| launch_error(&app_settings.pdf_to_printer_path, e))?;
+        } else if extension == "docx" || extension == "doc" {
+            let target_printer = printer_desc.as_str();
+            if get_printer_by_name(target_printer).is_none() {
+                error!("Printer '{}' not found.", target_printer);
+                return Err(AppError::printer("printer_not_found", format!("Printer '{}' not found.", target_printer))
+                    .with_printer(target_printer));
+            }
+
+            status = Command::new("powershell")
+                .arg("-NoProfile")
+                .arg("-NonInteractive")
+                .arg("-Command")
+                .arg(WORD_PRINT_SCRIPT)
+                .env("PRINT_DOCUMENT", &path)
+                .env("PRINT_PRINTER", target_printer)
+                .status()
+                .map_err(|e| launch_error("powershell", e))?;
+
+        } else if extension == "xlsx" || extension == "xls" { 
+            status = Command::new("powershell")
+                .arg("-Command")
+                .arg(format!(
+                    "Start-Process -FilePath '{}' -Verb Print",
+                    path.display()
+                ))
+                .status()
+                .map_err(|e| launch_error("powershell", e))?;
+            
+        } else if extension == "jpg" || extension == "png" {
+            status = Command::new("mspaint.exe")
+                .arg("/p")
+                .arg(path.display().to_string())
+                .status()
+                .map_err(|e| launch_error("mspaint.exe", e))?;
+        } else {
+            return Err(AppError::report("document_type_unsupported", format!("Can't print {} files", extension.to_string_lossy()))
+                .with_path(&path));
         }
-    } else if print_order_row.print_type.to_lowercase() == "final docs" {
-        //parse notes
-        let parts = print_order_row.notes.split("?");
-        let collection: Vec<&str> = parts.collect();
-        let mut search_path = collection.get(0).unwrap().to_string();
-        let report_name = collection.get(1).unwrap().to_string();
-        let parm1 = collection.get(2);
-        let parm2 = collection.get(3);
-        let parm3 = collection.get(4);
 
-        search_path = swap_drive(search_path);
+        info!("Process exited with status: {}", status);
+        check_status(status, &path, &printer_desc, &order)?;
+        copies = 1;
+    } else if print_order_row.print_type.to_lowercase() == "final docs" {
+        let printer_name = default_printer(&app_settings)?;
+        let Instruction::FinalDocs { search_path, report: report_name, params } = parse_notes(&print_order_row, &order)? else {
+            return Err(wrong_instruction(&print_order_row, &order, "a Final DOCS"));
+        };
+        let search_path = app_settings.resolve_path(&search_path);
 
         // serach for document
-        match finder(&search_path.as_str(), report_name.clone()) {
-            Ok(v) =>
-                for path in v {
-                    // for a specific reports that require first and last SN, and only print once
-                    if report_name == "01A000207-A01" || report_name == "01A000208-A01" || report_name == "01A000209-A01" {
-                        let snn = serial_number.parse::<i32>().unwrap() + (order.due_quantity as i32);
-                        let width = serial_number.len();
-                        let new_serial = format!("{:0width$}", snn, width = width);
-                        let status = Command::new(vc_exe_path)
-                                .arg("-e")
-                                .arg(path.display().to_string())
-                                .raw_arg(&format!("\"Parm1:{}\"", order.order_number))
-                                .raw_arg(&format!("\"Parm2:{}\"", serial_number))
-                                .raw_arg(&format!("\"Parm3:{}\"", new_serial))
-                                .raw_arg(&format!("\"Printer_Only:{}\"", printer_name))
-                                .status()
-                                .map_err(|e| format!("Failed to execute process: {}", e))?;
-                        println!("Process exited with status: {}", status);
-                        break;
-                    }
-                    for i in 0..(order.due_quantity  as i32) {
-                        let snn = serial_number.parse::<i32>().unwrap() + i;
-                        let width = serial_number.len();
-                        let new_serial = format!("{:0width$}", snn, width = width);
-
-                        let mut command = Command::new(vc_exe_path);
-                        command.arg("-e");
-                        command.arg(path.display().to_string());
-
-                        // Parm arguments
-                        command.raw_arg(&format!("\"Parm1:{}\"", order.order_number));
-                        command.raw_arg(&format!("\"Parm2:{}\"", new_serial));
-
-                        if let Some(a) = &parm1 {
-                            command.raw_arg(&format!("\"Parm3:{}\"", a));
-                        }
-                        if let Some(a) = &parm2 {
-                            command.raw_arg(&format!("\"Parm4:{}\"", a));
-                        }
-                        if let Some(a) = &parm3 {
-                            command.raw_arg(&format!("\"Parm5:{}\"", a));
-                        }
-
-                        command.raw_arg(&format!("\"Printer_Only:{}\"", printer_name));
-
-                        let status = command
-                            .status()
-                            .map_err(|e| format!("Failed to execute process: {}", e))?;
-
-                        if !status.success() {
-                            return Err(format!("Process exited with non-zero status: {}", status));
-                        }
-
-                        println!("Printed serial: {} (exit: {})", new_serial, status);
-                    }
-                    break;
-                },
-            Err(e) => println!("error finding file: {e:?}")
+        let path = match finder(&search_path, &report_name)? {
+            Some(path) => path,
+            None => return Ok(PrintOutcome::not_found(&search_path, &report_name)),
+        };
+        // for a specific reports that require first and last SN, and only print once
+        if first_last_report(&report_name) {
+            let first = serial_number.printed(app_settings.check_digit);
+            let next = next_serial.printed(app_settings.check_digit);
+            let report_command = || first_last_command(vc_exe_path, &path, &order, &first, &next);
+            let status = report_command()
+                    .raw_arg(&format!("\"Printer_Only:{}\"", printer_name))
+                    .status()
+                    .map_err(|e| launch_error(vc_exe_path, e))?;
+            info!("Process exited with status: {}", status);
+            check_status(status, &path, &printer_name, &order)?;
+            record_printed(&order, &print_order_row, &user, serial_number, units.clone(), reprint_run, ctx).await?;
+            // named by the Parm2 and Parm3 serials the report was sent
+            archive_failures = archive(vec![ArchiveCopy::new(report_command(), &order, &print_order_row, &[&first, &next])], &order, &app_settings).await;
+            copies = 1;
+        } else {
+            let to_print = units_to_print(&order, serial_number, quantity, &params, &app_settings, ctx)?;
+            let mut archive_copies = Vec::new();
+            let run: Result<(), AppError> = async {
+                for (unit, index) in to_print.iter().zip(units.clone()) {
+                    let mut command = unit_command(vc_exe_path, &path, &order, unit);
+                    command.raw_arg(&format!("\"Printer_Only:{}\"", printer_name));
+
+                    let status = command
+                        .status()
+                        .map_err(|e| launch_error(vc_exe_path, e))?;
+                    check_status(status, &path, &printer_name, &order)?;
+
+                    info!("Printed serial: {} (exit: {})", unit.printed_serial, status);
+                    record_printed(&order, &print_order_row, &user, unit.serial, index..index + 1, reprint_run, ctx).await?;
+                    archive_copies.push(ArchiveCopy::new(unit_command(vc_exe_path, &path, &order, unit), &order, &print_order_row, &[&unit.printed_serial]));
+                }
+                Ok(())
+            }.await;
+            // the units that printed before a failure are archived too
+            archive_failures = archive(archive_copies, &order, &app_settings).await;
+            run?;
+            copies = quantity;
         }
     } else {
         let output = format!("print did not match any printing option; {}", print_order_row.print_type);
-        return Err(output.to_string());
+        return Err(AppError::config("print_type_unknown", output).with_order(&order.order_number));
     }
+
+    Ok(PrintOutcome::Printed { copies, archive_failures })
     
-    // if final docs / label, count up the serial number, and record in the serial number tracker
-    if (print_order_row.print_type == "Final DOCS" || print_order_row.print_type.starts_with("94A") || print_order_row.print_type.starts_with("K94A")) && !reprint_run {
-        let snn = serial_number.parse::<i32>().unwrap() + (order.due_quantity as i32);
-        let width = serial_number.len();
-        let new_serial = format!("{:0width$}", snn, width = width);
-        match serial_number_files::serial_number_up(new_serial, &app_handle).await {
-            Ok(_) => println!("sn up success"),
-            Err(e) => return Err(format!("did not count sn up: {}", e)),
-        }
-        for i in 0..(order.due_quantity  as i32) {
-            let snn = serial_number.parse::<i32>().unwrap() + i;
-            let width = serial_number.len();
-            let new_serial = format!("{:0width$}", snn, width = width);
-            match serial_number_files::serial_number_tracker(order.part_number.clone(), order.assn_number.clone(), new_serial, user.clone(), &app_handle) {
-                Ok(_) => println!("sn tracker success"),
-                Err(e) => return Err(format!("did not write to tracker: {}", e)),
-            }
+}
+
+// labels / final docs that came out of the printer: count the serial number up past them, write them to the serial
+// number tracker and the print progress. called as each unit prints, so a run that stops half way still accounts for
+// the units it printed and the serial number never moves for a skipped document
+async fn record_printed(order: &Order, row: &PrintOrderRow, user: &str, first_serial: SerialNumber, units: Range<u32>, reprint_run: bool, ctx: &AppContext) -> Result<(), AppError> {
+    if reprint_run {
+        return Ok(());
+    }
+    let count = units.len() as u32;
+    match serial_number_files::serial_number_up(first_serial.checked_add(count)?, ctx).await {
+        Ok(_) => debug!("sn up success"),
+        Err(e) => return Err(AppError { message: format!("did not count sn up: {}", e.message), ..e }),
+    }
+    for new_serial in first_serial.range(count)? {
+        match serial_number_files::serial_number_tracker(order.part_number.clone(), order.assn_number.clone(), new_serial.to_string(), user.to_string(), ctx) {
+            Ok(_) => debug!("sn tracker success"),
+            Err(e) => return Err(AppError { message: format!("did not write to tracker: {}", e.message), ..e }),
         }
-    } 
-    Ok(format!("Success"))
-    
+    }
+    print_progress::record(&order.order_number, row, &units, ctx)
 }
 
-fn swap_drive(drive_path: String) -> String {
-    // drive swap to real name, to be changed
-    if drive_path.starts_with("P:\\") {
-        format!("\\\\pxsvsfs01\\Production{}", &drive_path[2..])
-    } else if drive_path.starts_with("Q:\\") {
-        format!("\\\\pxsvsfs01\\Quality{}", &drive_path[2..])
-    } else if drive_path.starts_with("R:\\") {
-        format!("\\\\pxsvsfs01\\Purchasing{}", &drive_path[2..])
-    } else if drive_path.starts_with("S:\\") {
-        format!("\\\\pxsvsfs01\\Sales & Marketing{}", &drive_path[2..])
-    } else if drive_path.starts_with("X:\\") {
-        format!("\\\\pxsvsfs01\\UserData{}", &drive_path[2..])
-    } else if drive_path.starts_with("Y:\\") {
-        format!("\\\\pxsvsfs01\\Engineering{}", &drive_path[2..])
-    } else {
-        drive_path
+// one label or final doc of a run
+struct Unit {
+    serial: SerialNumber,
+    printed_serial: String,
+    params: Vec<String>,
+}
+
+// serial and note parameters for every unit of the run, worked out before anything prints so a bad GS1 field
+// can't stop a run half way
+fn units_to_print(order: &Order, serial_number: SerialNumber, quantity: u32, params: &[String], app_settings: &Settings, ctx: &AppContext) -> Result<Vec<Unit>, AppError> {
+    let today = ctx.now().date_naive();
+    serial_number
+        .range(quantity)?
+        .map(|serial| {
+            let printed_serial = serial.printed(app_settings.check_digit);
+            let params = gs1::expand_params(params, order, &printed_serial, today)?;
+            Ok(Unit { serial, printed_serial, params })
+        })
+        .collect()
+}
+
+// Visual CUT run for one unit, the caller adds where the output goes
+fn unit_command(vc_exe_path: &str, path: &Path, order: &Order, unit: &Unit) -> Command {
+    let mut command = Command::new(vc_exe_path);
+    command.arg("-e");
+    command.arg(path.display().to_string());
+
+    // Parm arguments
+    command.raw_arg(&format!("\"Parm1:{}\"", order.order_number));
+    command.raw_arg(&format!("\"Parm2:{}\"", unit.printed_serial));
+    for (i, a) in unit.params.iter().enumerate() {
+        command.raw_arg(&format!("\"Parm{}:{}\"", i + 3, a));
     }
+    command
+}
+
+// reports that print once per run with the run's first serial and the serial after it
+fn first_last_report(report_name: &str) -> bool {
+    report_name == "01A000207-A01" || report_name == "01A000208-A01" || report_name == "01A000209-A01"
 }
 
-fn set_default_printer(printer_name: &str) -> std::io::Result<()> {
-    // sets default printer
-    Command::new("RUNDLL32")
-        .arg("PRINTUI.DLL,PrintUIEntry")
-        .arg("/y")
-        .arg("/n")
-        .arg(printer_name)
-        .status()?
-        .success()
-        .then(|| ())
-        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "Failed to set default printer"))
+// Visual CUT run for a first/last report, the caller adds where the output goes
+fn first_last_command(vc_exe_path: &str, path: &Path, order: &Order, first: &str, next: &str) -> Command {
+    let mut command = Command::new(vc_exe_path);
+    command
+        .arg("-e")
+        .arg(path.display().to_string())
+        .raw_arg(&format!("\"Parm1:{}\"", order.order_number))
+        .raw_arg(&format!("\"Parm2:{}\"", first))
+        .raw_arg(&format!("\"Parm3:{}\"", next));
+    command
 }
 
-fn fetch_default_printer_wmic() -> Option<String> {
-    // gets current windows default printer
-    println!("Loading default printer...");
-    let output = Command::new("wmic")
-        .args(["printer", "get", "name,", "default"])
-        .output()
-        .expect("Failed to run wmic");
+fn default_params(report_name: &str, params: &mut Vec<String>) {
+    // forced to default value indicator $ (setup in crystal reports)
+    if report_name == "01A000038-A01" || report_name == "01A000039-A01" || report_name == "01A000052-A01" {
+        if params.is_empty() {
+            params.push("$".to_string());
+        }
+    }
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+#[tauri::command]
+pub async fn preview_label(order: Order, print_order_row: PrintOrderRow, serial_number: String, app_handle: AppHandle) -> Result<Response, AppError> {
+    let pdf = render_preview(order, print_order_row, SerialNumber::parse(&serial_number)?, &AppContext::from_app(&app_handle)?)?;
+    let bytes = fs::read(&pdf)
+        .map_err(|e| AppError::io("preview_read", format!("Failed to read preview: {}", e)).with_path(&pdf))?;
+    Ok(Response::new(bytes))
+}
 
-    for line in stdout.lines() {
-        let line = line.trim();
-        if line.to_ascii_lowercase().starts_with("default") || line.is_empty() {
-            continue; // skip header
+// a label or Final DOCS row exported to a PDF in the previews folder instead of printed, with the command a print
+// of one unit from serial_number would send. nothing is counted or tracked
+pub fn render_preview(order: Order, print_order_row: PrintOrderRow, serial_number: SerialNumber, ctx: &AppContext) -> Result<PathBuf, AppError> {
+    let app_settings = ctx.settings()?.with_resolved_paths();
+    let (search_path, report_name, params, first_last) = match parse_notes(&print_order_row, &order)? {
+        Instruction::Label { report, mut params } => {
+            default_params(&report, &mut params);
+            (app_settings.label_path.clone(), report, params, false)
+        }
+        Instruction::FinalDocs { search_path, report, params } => {
+            let first_last = first_last_report(&report);
+            (app_settings.resolve_path(&search_path), report, params, first_last)
+        }
+        Instruction::InitialDocs { .. } => {
+            return Err(AppError::config("preview_unsupported", "Only labels and Final DOCS can be previewed").with_order(&order.order_number));
         }
+    };
+    let path = finder(&search_path, &report_name)?
+        .ok_or_else(|| AppError::not_found("document_not_found", format!("Nothing matching {} in {}", report_name, search_path)).with_order(&order.order_number))?;
+    let printed_serial = serial_number.printed(app_settings.check_digit);
+    let command = if first_last {
+        let next = serial_number.checked_add(1)?.printed(app_settings.check_digit);
+        first_last_command(VC_EXE_PATH, &path, &order, &printed_serial, &next)
+    } else {
+        let unit = units_to_print(&order, serial_number, 1, &params, &app_settings, ctx)?.remove(0);
+        unit_command(VC_EXE_PATH, &path, &order, &unit)
+    };
+
+    let preview_dir = ctx.file(PREVIEW_DIR);
+    fs::create_dir_all(&preview_dir)
+        .map_err(|e| AppError::io("preview_write", format!("Failed to create previews folder: {}", e)).with_path(&preview_dir))?;
+    let pdf = preview_dir.join(format!("{}-{}-{}.pdf", order.order_number.trim(), print_order_row.id, serial_number));
+    export_pdf(command, &pdf)
+        .map_err(|e| e.with_order(&order.order_number))?;
+
+    info!("Previewed serial: {} to {}", printed_serial, pdf.display());
+    Ok(pdf)
+}
 
-        if line.contains("TRUE") {
-            // skip column TRUE
-            let parts: Vec<&str> = line.splitn(2, "TRUE").collect();
-            if parts.len() == 2 {
-                let printer_name = parts[1].trim();
-                return Some(printer_name.to_string());
-            }
+// previews are only looked at once, so the folder is emptied each time the app starts
+pub fn clear_previews(ctx: &AppContext) {
+    let preview_dir = ctx.file(PREVIEW_DIR);
+    let Ok(entries) = fs::read_dir(&preview_dir) else { return };
+    for entry in entries.filter_map(Result::ok) {
+        if let Err(e) = fs::remove_file(entry.path()) {
+            warn!("Failed to remove old preview {}: {}", entry.path().display(), e);
         }
     }
+}
+
+// runs a Visual CUT command with the report exported to a pdf instead of printed, an error unless the file turns up
+fn export_pdf(mut command: Command, pdf: &Path) -> Result<(), AppError> {
+    // an old file left behind would pass for this one if Visual CUT fails quietly
+    if pdf.exists() {
+        fs::remove_file(pdf).map_err(|e| AppError::io("pdf_write", format!("Failed to remove old pdf: {}", e)).with_path(pdf))?;
+    }
+
+    let status = command
+        .raw_arg("\"Export_Format:Adobe Acrobat (pdf)\"")
+        .raw_arg(&format!("\"Export_File:{}\"", pdf.display()))
+        .status()
+        .map_err(|e| launch_error(VC_EXE_PATH, e))?;
+    if !status.success() || !pdf.exists() {
+        return Err(AppError::report("pdf_export_failed", format!("Visual CUT did not export a pdf (exit: {})", status))
+            .with_path(pdf));
+    }
+    Ok(())
+}
 
-    None
+// a pdf copy of a printed BOM, SNL or Final DOCS report for quality
+struct ArchiveCopy {
+    command: Command,
+    name: String,
 }
 
-fn get_default_printer_cached() -> Option<&'static str> {
-    //caches the default printer, if the user decides to print mulitple times -> saves on runtime 2nd run
-    // we could consider saving default printer like CLR printer, but could run into issue when user wants to change it, they have to change in windows and our app. 
-    DEFAULT_PRINTER
-        .get_or_init(|| fetch_default_printer_wmic().unwrap_or_else(|| "Unknown".to_string()))
-        .as_str()
-        .into()
+impl ArchiveCopy {
+    fn new(command: Command, order: &Order, row: &PrintOrderRow, serials: &[&str]) -> Self {
+        ArchiveCopy { command, name: archive_name(&order.order_number, row, serials) }
+    }
 }
 
-fn finder(root_dir: &str, search_term: String) -> Result<Vec<PathBuf>, String> {
-    // finds all files in root_dir that have serach_term
-    let mut files = Vec::new();
+// named by order, row and the serials sent to the report, or the print type for reports without serials
+fn archive_name(order_number: &str, row: &PrintOrderRow, serials: &[&str]) -> String {
+    let covers = if serials.is_empty() { row.print_type.trim().to_string() } else { serials.join("-") };
+    format!("{}-{}-{}.pdf", order_number.trim(), row.id, covers)
+}
 
-    for entry in WalkDir::new(root_dir)
-        .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_file())
-    {
-        let file_name = entry.file_name().to_string_lossy();
-
-        if file_name.contains(search_term.as_str()) {
-            println!("path: {}", entry.path().display());
-            files.push(entry.path().to_path_buf());
+// exports a run's copies to the order's archive folder one after another, once the run has printed so Visual CUT
+// running a second time doesn't hold up the next unit. the print already went through, so a failed export doesn't
+// fail it, the failures go back with the outcome for the window to show
+async fn archive(copies: Vec<ArchiveCopy>, order: &Order, app_settings: &Settings) -> Vec<AppError> {
+    if app_settings.archive_path.trim().is_empty() || copies.is_empty() {
+        return Vec::new();
+    }
+    let order_number = order.order_number.clone();
+    let folder = order_archive_folder(&order_number, app_settings);
+    let exported = tokio::task::spawn_blocking(move || {
+        let folder = folder.and_then(|folder| {
+            fs::create_dir_all(&folder)
+                .map_err(|e| AppError::io("pdf_write", format!("Failed to create archive folder: {}", e)).with_path(&folder))?;
+            Ok(folder)
+        });
+        let folder = match folder {
+            Ok(folder) => folder,
+            Err(e) => return vec![e],
+        };
+        let mut failures = Vec::new();
+        for copy in copies {
+            match export_pdf(copy.command, &folder.join(&copy.name)) {
+                Ok(()) => info!("Archived {}", copy.name),
+                Err(e) => failures.push(e),
+            }
         }
+        failures
+    })
+    .await
+    .unwrap_or_else(|e| vec![AppError::io("pdf_write", format!("Archive export stopped: {}", e))]);
+
+    exported
+        .into_iter()
+        .map(|e| {
+            error!("Failed to archive for order {}: {}", order_number, e.message);
+            e.with_order(&order_number)
+        })
+        .collect()
+}
+
+// where the pdf copies of an order's reports go
+pub fn order_archive_folder(order_number: &str, app_settings: &Settings) -> Result<PathBuf, AppError> {
+    if app_settings.archive_path.trim().is_empty() {
+        return Err(AppError::config("archive_unset", "No PDF archive folder is set in the settings"));
+    }
+    Ok(Path::new(app_settings.archive_path.trim()).join(order_number.trim()))
+}
+
+#[tauri::command]
+pub fn open_order_archive(order_number: String, app_handle: AppHandle) -> Result<(), AppError> {
+    let app_settings = AppContext::from_app(&app_handle)?.settings()?.with_resolved_paths();
+    let folder = order_archive_folder(&order_number, &app_settings)?;
+    if !folder.is_dir() {
+        return Err(AppError::not_found("archive_not_found", format!("Nothing has been archived for order {} yet", order_number.trim()))
+            .with_path(&folder)
+            .with_order(&order_number));
     }
+    app_handle
+        .opener()
+        .open_path(folder.display().to_string(), None::<&str>)
+        .map_err(|e| AppError::io("archive_open", format!("Failed to open archive folder: {}", e)).with_path(&folder))
+}
 
-    Ok(files)
+fn launch_error(program: &str, e: std::io::Error) -> AppError {
+    AppError::report("process_launch", format!("Failed to execute process: {}", e)).with_path(program)
+}
+
+fn check_status(status: ExitStatus, path: impl AsRef<Path>, printer: &str, order: &Order) -> Result<(), AppError> {
+    if status.success() {
+        return Ok(());
+    }
+    Err(AppError::report("process_status", format!("Process exited with non-zero status: {}", status))
+        .with_path(path)
+        .with_printer(printer)
+        .with_order(&order.order_number))
+}
+
+fn parse_notes(print_order_row: &PrintOrderRow, order: &Order) -> Result<Instruction, AppError> {
+    notes::parse(&print_order_row.print_type, &print_order_row.notes)
+        .map_err(|e| e.with_order(&order.order_number))
+}
+
+// the row's print type and its note kind disagree, notes::NoteKind and the print branches have drifted apart
+fn wrong_instruction(print_order_row: &PrintOrderRow, order: &Order, expected: &str) -> AppError {
+    AppError::config("note_kind_mismatch", format!("{} note did not read as {} note", print_order_row.print_type.trim(), expected))
+        .with_order(&order.order_number)
+}
+
+fn finder(root_dir: &str, search_term: &str) -> Result<Option<PathBuf>, AppError> {
+    // looks the document up in the index for root_dir, errors when more than one file could be meant
+    doc_index::find_document(root_dir, search_term)
+}
+
+// printer for the BOM, SNL, config sheets and Final DOCS: the Windows default, read on every print so a default
+// changed in Windows while the app is open is picked up, else the fallback printer from the settings
+pub fn default_printer(app_settings: &Settings) -> Result<String, AppError> {
+    if let Some(printer) = get_default_printer() {
+        debug!("Default Printer: {}", printer.name);
+        return Ok(printer.name);
+    }
+    let fallback = app_settings.fallback_printer.trim();
+    if fallback.is_empty() {
+        return Err(AppError::printer("no_default_printer", "Windows has no default printer and no fallback printer is set in the settings"));
+    }
+    warn!("Windows has no default printer, using the fallback printer {}", fallback);
+    Ok(fallback.to_string())
 }
 
 #[tauri::command]
-pub async fn check_printer_regex(printer: String) -> Result<bool, String> {
+pub async fn check_printer_regex(printer: String) -> Result<bool, AppError> {
     // output list of printers
     let printer_list = get_printers();
 
     // gets list of printers available
     let counter = printer_list.iter().any(|p| {
-        println!("{:?}", p.name);
+        trace!("{:?}", p.name);
         p.name.to_ascii_lowercase() == printer.to_ascii_lowercase()
     });
-    println!("");
 
     Ok(counter)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::temp_context;
+    use chrono::Local;
+
+    #[test]
+    fn default_params_fill_in_the_default_indicator() {
+        let cases = [
+            ("01A000038-A01", vec![], vec!["$"]),
+            ("01A000052-A01", vec![], vec!["$"]),
+            ("01A000039-A01", vec!["Lot 7"], vec!["Lot 7"]),
+            ("01A000040-A01", vec![], vec![]),
+        ];
+
+        for (report, params, expected) in cases {
+            let mut params: Vec<String> = params.into_iter().map(String::from).collect();
+            default_params(report, &mut params);
+            assert_eq!(params, expected, "{}", report);
+        }
+    }
+
+    #[test]
+    fn only_the_first_last_reports_print_once_per_run() {
+        assert!(first_last_report("01A000207-A01"));
+        assert!(first_last_report("01A000209-A01"));
+        assert!(!first_last_report("01A000207-A02"));
+        assert!(!first_last_report("01A000038-A01"));
+    }
+
+    fn row(id: i32, print_type: &str) -> PrintOrderRow {
+        PrintOrderRow { id, print_type: print_type.to_string(), notes: String::new() }
+    }
+
+    #[test]
+    fn archive_copies_are_named_by_the_serials_sent() {
+        assert_eq!(archive_name("5000001 ", &row(1, "BOM"), &[]), "5000001-1-BOM.pdf");
+        assert_eq!(archive_name("5000001", &row(3, " SNL "), &[]), "5000001-3-SNL.pdf");
+        assert_eq!(archive_name("5000001", &row(7, "Final DOCS"), &["001010198K"]), "5000001-7-001010198K.pdf");
+        // a first/last report is sent the first serial and the one after the run
+        assert_eq!(archive_name("5000001", &row(7, "Final DOCS"), &["001010198", "001010201"]), "5000001-7-001010198-001010201.pdf");
+    }
+
+    #[test]
+    fn archive_folder_is_one_per_order() {
+        let mut app_settings = temp_context("archive-folder", Local::now()).settings().unwrap();
+        app_settings.archive_path = " ".to_string();
+        assert_eq!(order_archive_folder("5000001", &app_settings).unwrap_err().code, "archive_unset");
+
+        app_settings.archive_path = " Q:\\Archive ".to_string();
+        assert_eq!(order_archive_folder(" 5000001 ", &app_settings).unwrap(), Path::new("Q:\\Archive").join("5000001"));
+    }
+
+    #[test]
+    fn old_previews_are_cleared() {
+        let ctx = temp_context("clear-previews", Local::now());
+        // nothing to clear before the first preview
+        clear_previews(&ctx);
+
+        let preview_dir = ctx.file(PREVIEW_DIR);
+        fs::create_dir_all(&preview_dir).unwrap();
+        fs::write(preview_dir.join("5000001-4-001010198.pdf"), "pdf").unwrap();
+        clear_previews(&ctx);
+
+        assert!(preview_dir.is_dir());
+        assert_eq!(fs::read_dir(&preview_dir).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::ops::Range;
+use std::sync::Mutex;
+use log::warn;
+use tauri::AppHandle;
+use crate::context::AppContext;
+use crate::notes::{self, Instruction};
+use crate::settings::{write_json, Settings};
+use crate::structs::{Order, PrintBatch, PrintOrderRow};
+use crate::error::AppError;
+
+// units printed so far for every label and Final DOCS row, so a lot built in batches carries on where the
+// last print stopped. keyed by order number, then by row_key
+const PRINT_PROGRESS_FILE: &str = "printProgress.json";
+
+type PrintProgress = BTreeMap<String, BTreeMap<String, u32>>;
+
+// the window prints its rows at the same time, every read and read-modify-write of the file holds this
+static PROGRESS_LOCK: Mutex<()> = Mutex::new(());
+
+// an order can have several Final DOCS rows, the report each one prints tells them apart. only the report counts so
+// fixing a parameter or the folder in the note keeps the row's progress. a note that doesn't parse won't print, its
+// text stands in
+pub fn row_key(row: &PrintOrderRow) -> String {
+    let identity = match notes::parse(&row.print_type, &row.notes) {
+        Ok(Instruction::Label { report, .. }) | Ok(Instruction::FinalDocs { report, .. }) => report,
+        _ => row.notes.trim().to_string(),
+    };
+    format!("{}: {}", row.print_type.trim(), identity)
+}
+
+#[tauri::command]
+pub fn get_print_progress(order_number: String, rows: Vec<PrintOrderRow>, app_handle: AppHandle) -> Result<BTreeMap<i32, u32>, AppError> {
+    rows_progress(&order_number, &rows, &AppContext::from_app(&app_handle)?)
+}
+
+// units printed for each of the window's rows by row id, so the window doesn't have to know how rows are keyed
+pub fn rows_progress(order_number: &str, rows: &[PrintOrderRow], ctx: &AppContext) -> Result<BTreeMap<i32, u32>, AppError> {
+    let progress = order_progress(order_number, ctx)?;
+    Ok(rows.iter().filter_map(|row| progress.get(&row_key(row)).map(|printed| (row.id, *printed))).collect())
+}
+
+// units printed for each row of the order, rows never printed are left out
+pub fn order_progress(order_number: &str, ctx: &AppContext) -> Result<BTreeMap<String, u32>, AppError> {
+    let _lock = PROGRESS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(load(ctx)?.remove(order_number.trim()).unwrap_or_default())
+}
+
+// the units of the order this print covers. starts after the units already printed unless the batch says otherwise,
+// and covers the rest of the order unless it gives a quantity. printing past the due quantity, starting after units
+// not printed yet, or over units already printed outside a reprint run, needs initials from the supervisor list in
+// the settings
+pub fn plan(order: &Order, row: &PrintOrderRow, batch: &PrintBatch, reprint_run: bool, settings: &Settings, ctx: &AppContext) -> Result<Range<u32>, AppError> {
+    let due = order.units(settings.fractional_quantity)?;
+    let printed = order_progress(&order.order_number, ctx)?.get(&row_key(row)).copied().unwrap_or(0);
+    let start = batch.start.unwrap_or(if reprint_run { 0 } else { printed });
+    let quantity = batch.quantity.unwrap_or(due.saturating_sub(start));
+    if quantity == 0 {
+        return Err(AppError::config("print_quantity_none", format!("Nothing to print for {}, {} of {} units are already printed", row.print_type, printed, due))
+            .with_order(&order.order_number));
+    }
+    let units = start..start.saturating_add(quantity);
+
+    let problem = if units.end > due {
+        format!("Units {} to {} go past the due quantity of {}", units.start + 1, units.end, due)
+    } else if units.start > printed {
+        // progress is a count, units skipped now would count as printed once this batch is recorded
+        format!("Units {} to {} aren't printed yet and would be skipped", printed + 1, units.start)
+    } else if !reprint_run && units.start < printed {
+        format!("Units {} to {} are already printed", units.start + 1, printed.min(units.end))
+    } else {
+        return Ok(units);
+    };
+
+    match batch.supervisor.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        None => Err(AppError::config("print_quantity_exceeded", format!("{} for {}, a supervisor has to approve", problem, row.print_type))
+            .with_order(&order.order_number)),
+        Some(supervisor) if !settings.supervisors.iter().any(|s| !s.trim().is_empty() && s.trim().eq_ignore_ascii_case(supervisor)) => {
+            Err(AppError::config("supervisor_unknown", format!("'{}' is not in the supervisor list in the settings", supervisor))
+                .with_order(&order.order_number))
+        }
+        Some(supervisor) => {
+            warn!("{} for {} on order {}, approved by {}", problem, row.print_type, order.order_number, supervisor);
+            Ok(units)
+        }
+    }
+}
+
+// called as units print, progress only moves forward so reprinting earlier units leaves it alone
+pub fn record(order_number: &str, row: &PrintOrderRow, units: &Range<u32>, ctx: &AppContext) -> Result<(), AppError> {
+    let _lock = PROGRESS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut progress = load(ctx)?;
+    let printed = progress
+        .entry(order_number.trim().to_string())
+        .or_default()
+        .entry(row_key(row))
+        .or_default();
+    *printed = (*printed).max(units.end);
+
+    write_json(&ctx.file(PRINT_PROGRESS_FILE), &progress)
+}
+
+// callers hold PROGRESS_LOCK
+fn load(ctx: &AppContext) -> Result<PrintProgress, AppError> {
+    let file_path = ctx.file(PRINT_PROGRESS_FILE);
+    if !fs::exists(&file_path).map_err(|e| AppError::io("print_progress_read", format!("Can't check existence of printProgress: {}", e)).with_path(&file_path))? {
+        return Ok(PrintProgress::new());
+    }
+
+    let file = File::open(&file_path)
+        .map_err(|e| AppError::io("print_progress_read", format!("Failed to open print progress: {}", e)).with_path(&file_path))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| AppError::config("print_progress_parse", format!("Failed to parse print progress: {}", e)).with_path(&file_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::{order, temp_context};
+    use chrono::Local;
+
+    fn label() -> PrintOrderRow {
+        PrintOrderRow { id: 5, print_type: "94A000003".to_string(), notes: "ProductLabel?Rev B?Lot 7".to_string() }
+    }
+
+    fn batch(quantity: Option<u32>, start: Option<u32>, supervisor: Option<&str>) -> PrintBatch {
+        PrintBatch { quantity, start, supervisor: supervisor.map(String::from) }
+    }
+
+    #[test]
+    fn batches_carry_on_from_the_units_already_printed() {
+        let ctx = temp_context("print-progress-batches", Local::now());
+        let settings = ctx.settings().unwrap();
+
+        let first = plan(&order(50.0), &label(), &batch(Some(20), None, None), false, &settings, &ctx).unwrap();
+        assert_eq!(first, 0..20);
+        record("5000001", &label(), &first, &ctx).unwrap();
+
+        let rest = plan(&order(50.0), &label(), &PrintBatch::default(), false, &settings, &ctx).unwrap();
+        assert_eq!(rest, 20..50);
+        record("5000001", &label(), &rest, &ctx).unwrap();
+
+        assert_eq!(order_progress("5000001", &ctx).unwrap().get(&row_key(&label())), Some(&50));
+        assert_eq!(plan(&order(50.0), &label(), &PrintBatch::default(), false, &settings, &ctx).unwrap_err().code, "print_quantity_none");
+    }
+
+    #[test]
+    fn going_past_the_due_quantity_needs_a_supervisor() {
+        let ctx = temp_context("print-progress-override", Local::now());
+        let mut settings = ctx.settings().unwrap();
+        settings.supervisors = vec!["JD".to_string()];
+
+        let over = batch(Some(12), None, None);
+        assert_eq!(plan(&order(10.0), &label(), &over, false, &settings, &ctx).unwrap_err().code, "print_quantity_exceeded");
+
+        let unknown = batch(Some(12), None, Some("XY"));
+        assert_eq!(plan(&order(10.0), &label(), &unknown, false, &settings, &ctx).unwrap_err().code, "supervisor_unknown");
+
+        let approved = batch(Some(12), None, Some("jd"));
+        assert_eq!(plan(&order(10.0), &label(), &approved, false, &settings, &ctx).unwrap(), 0..12);
+    }
+
+    #[test]
+    fn skipping_units_needs_a_supervisor() {
+        let ctx = temp_context("print-progress-gap", Local::now());
+        let mut settings = ctx.settings().unwrap();
+        settings.supervisors = vec!["JD".to_string()];
+        record("5000001", &label(), &(0..10), &ctx).unwrap();
+
+        let gap = batch(Some(5), Some(20), None);
+        let e = plan(&order(50.0), &label(), &gap, false, &settings, &ctx).unwrap_err();
+        assert_eq!(e.code, "print_quantity_exceeded");
+        assert!(e.message.contains("Units 11 to 20"), "{}", e.message);
+        assert_eq!(plan(&order(50.0), &label(), &gap, true, &settings, &ctx).unwrap_err().code, "print_quantity_exceeded");
+
+        let approved = batch(Some(5), Some(20), Some("JD"));
+        assert_eq!(plan(&order(50.0), &label(), &approved, false, &settings, &ctx).unwrap(), 20..25);
+        // carrying on right where the last batch stopped is not a gap
+        assert_eq!(plan(&order(50.0), &label(), &batch(Some(5), Some(10), None), false, &settings, &ctx).unwrap(), 10..15);
+    }
+
+    #[test]
+    fn editing_a_note_keeps_the_rows_progress() {
+        let ctx = temp_context("print-progress-note-edit", Local::now());
+        record("5000001", &label(), &(0..5), &ctx).unwrap();
+
+        let edited = PrintOrderRow { notes: "ProductLabel ?Rev C?Lot 7".to_string(), ..label() };
+        assert_eq!(row_key(&edited), row_key(&label()));
+        assert_eq!(rows_progress("5000001", &[edited], &ctx).unwrap().get(&5), Some(&5));
+
+        // another report is another row, and two Final DOCS rows keep apart
+        let other = PrintOrderRow { notes: "BoxLabel?Rev B".to_string(), ..label() };
+        assert_ne!(row_key(&other), row_key(&label()));
+        let final_docs = |notes: &str| PrintOrderRow { id: 9, print_type: "Final DOCS".to_string(), notes: notes.to_string() };
+        assert_eq!(row_key(&final_docs("K:/Docs?01A000207?Rev A")), row_key(&final_docs("K:/Final Docs?01A000207")));
+        assert_ne!(row_key(&final_docs("K:/Docs?01A000207")), row_key(&final_docs("K:/Docs?01A000208")));
+    }
+
+    #[test]
+    fn rows_printing_at_once_keep_their_progress() {
+        let ctx = temp_context("print-progress-concurrent", Local::now());
+        let rows: Vec<PrintOrderRow> = (0..8)
+            .map(|i| PrintOrderRow { id: i + 4, print_type: format!("94A00000{}", i), notes: "ProductLabel".to_string() })
+            .collect();
+
+        std::thread::scope(|scope| {
+            for row in &rows {
+                let ctx = &ctx;
+                scope.spawn(move || {
+                    for unit in 0..5 {
+                        record("5000001", row, &(unit..unit + 1), ctx).unwrap();
+                    }
+                });
+            }
+        });
+
+        let progress = order_progress("5000001", &ctx).unwrap();
+        assert!(rows.iter().all(|row| progress.get(&row_key(row)) == Some(&5)), "{:?}", progress);
+    }
+
+    #[test]
+    fn only_reprint_runs_go_over_printed_units() {
+        let ctx = temp_context("print-progress-reprint", Local::now());
+        let settings = ctx.settings().unwrap();
+        record("5000001", &label(), &(0..5), &ctx).unwrap();
+
+        let again = batch(Some(2), Some(3), None);
+        assert_eq!(plan(&order(10.0), &label(), &again, false, &settings, &ctx).unwrap_err().code, "print_quantity_exceeded");
+        assert_eq!(plan(&order(10.0), &label(), &again, true, &settings, &ctx).unwrap(), 3..5);
+
+        // a reprint of earlier units doesn't move the progress back
+        record("5000001", &label(), &(3..5), &ctx).unwrap();
+        assert_eq!(order_progress("5000001", &ctx).unwrap().get(&row_key(&label())), Some(&5));
+    }
+}
@@ -0,0 +1,326 @@
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use futures::future::join_all;
+use log::{debug, warn};
+use printers::common::base::printer::{Printer, PrinterState};
+use printers::get_printer_by_name;
+use serde::Serialize;
+use tauri::AppHandle;
+use crate::context::AppContext;
+use crate::notes::{self, Instruction, NoteKind, PrinterHint};
+use crate::print;
+use crate::settings::Settings;
+use crate::structs::PrintOrderRow;
+use crate::error::AppError;
+
+// network Zebras take ZPL on the raw port and answer ~HS (host status) with three STX ... ETX strings
+const ZEBRA_PORT: u16 = 9100;
+const HOST_STATUS: &[u8] = b"~HS";
+const ZEBRA_TIMEOUT: Duration = Duration::from_secs(2);
+const STX: char = '\u{2}';
+const ETX: char = '\u{3}';
+
+// spooler reasons that only mean the printer is idle or busy, anything else needs someone to look at it.
+// the printers crate reports "none" for a ready printer with no status bits set
+const BUSY_REASONS: &[&str] = &["none", "ready", "printing", "io_active", "busy", "processing", "waiting", "initializing", "warming_up", "power_save"];
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    Ready,
+    Printing,
+    Paused,
+    Offline,
+    Unknown,
+    NotInstalled,
+}
+
+// what the spooler, and the printer itself for network Zebras, say about one printer
+#[derive(Serialize, Debug)]
+pub struct PrinterHealth {
+    pub role: String, // the setting or print type that uses the printer
+    pub name: String,
+    pub state: State,
+    pub problems: Vec<String>, // empty when it's fine to print
+}
+
+// the flags of a ~HS answer worth stopping a print for
+#[derive(Debug, Default, PartialEq)]
+struct HostStatus {
+    paper_out: bool,
+    paused: bool,
+    buffer_full: bool,
+    corrupt_ram: bool,
+    under_temperature: bool,
+    over_temperature: bool,
+    head_open: bool,
+    ribbon_out: bool,
+}
+
+impl HostStatus {
+    fn problems(&self) -> Vec<String> {
+        [
+            (self.paper_out, "out of labels"),
+            (self.paused, "paused on the printer"),
+            (self.buffer_full, "receive buffer full"),
+            (self.corrupt_ram, "corrupt RAM"),
+            (self.under_temperature, "head too cold"),
+            (self.over_temperature, "head too hot"),
+            (self.head_open, "print head open"),
+            (self.ribbon_out, "out of ribbon"),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, problem)| problem.to_string())
+        .collect()
+    }
+}
+
+// printers the selected rows will print to that have a problem, checked before a print starts
+#[tauri::command]
+pub async fn check_print_health(print_order_rows: Vec<PrintOrderRow>, app_handle: AppHandle) -> Result<Vec<PrinterHealth>, AppError> {
+    let settings = AppContext::from_app(&app_handle)?.settings()?;
+    Ok(rows_health(&print_order_rows, &settings)
+        .await
+        .into_iter()
+        .filter(|health| !health.problems.is_empty())
+        .collect())
+}
+
+// every printer in the settings and the default printer, problems or not
+#[tauri::command]
+pub async fn printer_dashboard(app_handle: AppHandle) -> Result<Vec<PrinterHealth>, AppError> {
+    Ok(dashboard(&AppContext::from_app(&app_handle)?.settings()?).await)
+}
+
+pub async fn dashboard(settings: &Settings) -> Vec<PrinterHealth> {
+    let default = print::default_printer(settings);
+    let mut printers = Vec::new();
+    if let Ok(name) = &default {
+        printers.push(("default".to_string(), name.clone()));
+    }
+    printers.extend(settings.printers().iter().map(|(role, name)| (role.to_string(), name.to_string())));
+
+    let mut health = check_printers(printers, settings).await;
+    if let Err(e) = default {
+        health.insert(0, PrinterHealth { role: "default".to_string(), name: String::new(), state: State::NotInstalled, problems: vec![e.message] });
+    }
+    health
+}
+
+// each printer the rows use once, under the first row's print type
+pub async fn rows_health(rows: &[PrintOrderRow], settings: &Settings) -> Vec<PrinterHealth> {
+    let mut printers: Vec<(String, String)> = Vec::new();
+    for row in rows {
+        if let Some(name) = row_printer(row, settings) {
+            if !printers.iter().any(|(_, p)| p.eq_ignore_ascii_case(&name)) {
+                printers.push((row.print_type.clone(), name));
+            }
+        }
+    }
+    check_printers(printers, settings).await
+}
+
+// printers are checked side by side on blocking threads, the spooler call and the ~HS query both block
+async fn check_printers(printers: Vec<(String, String)>, settings: &Settings) -> Vec<PrinterHealth> {
+    let checks = printers.into_iter().map(|(role, name)| {
+        let zebra_host = settings.zebra_host(&name).map(String::from);
+        async move {
+            let (check_role, check_name) = (role.clone(), name.clone());
+            tokio::task::spawn_blocking(move || printer_health(&check_role, &check_name, zebra_host.as_deref()))
+                .await
+                .unwrap_or_else(|e| PrinterHealth { role, name, state: State::Unknown, problems: vec![format!("Printer check failed: {}", e)] })
+        }
+    });
+    join_all(checks).await
+}
+
+// the printer print sends a row to, None when the row can't be matched to one or there is no default printer
+// (print reports those itself)
+fn row_printer(row: &PrintOrderRow, settings: &Settings) -> Option<String> {
+    match NoteKind::of(&row.print_type) {
+        Some(NoteKind::Label) => settings.label_printer(&row.print_type).map(String::from),
+        Some(NoteKind::InitialDocs) => match notes::parse(&row.print_type, &row.notes).ok()? {
+            Instruction::InitialDocs { printer: PrinterHint::Clr, .. } => Some(settings.clr_printer.clone()),
+            Instruction::InitialDocs { printer: PrinterHint::Named(name), .. } => Some(name),
+            _ => print::default_printer(settings).ok(),
+        },
+        _ => print::default_printer(settings).ok(),
+    }
+}
+
+// zebra_host is the Zebra's address from the settings, for printers shared from a print server
+pub fn printer_health(role: &str, name: &str, zebra_host: Option<&str>) -> PrinterHealth {
+    let Some(printer) = get_printer_by_name(name) else {
+        return PrinterHealth {
+            role: role.to_string(),
+            name: name.to_string(),
+            state: State::NotInstalled,
+            problems: vec!["not installed".to_string()],
+        };
+    };
+
+    let state = match printer.state {
+        PrinterState::READY => State::Ready,
+        PrinterState::PRINTING => State::Printing,
+        PrinterState::PAUSED => State::Paused,
+        PrinterState::OFFLINE => State::Offline,
+        PrinterState::UNKNOWN => State::Unknown,
+    };
+    let mut problems = spooler_problems(&printer.state_reasons);
+    for (stopped, reason) in [(State::Paused, "paused"), (State::Offline, "offline")] {
+        if state == stopped && !problems.iter().any(|p| p == reason) {
+            problems.push(reason.to_string());
+        }
+    }
+
+    // the spooler says ready while a Zebra sits paused or out of labels, so ask the printer
+    let address = match zebra_host {
+        Some(host) => Some(host_address(host)),
+        None => zebra_address(&printer).map(Ok),
+    };
+    if let Some(address) = address {
+        match address.and_then(query_zebra) {
+            Ok(status) => problems.extend(status.problems()),
+            Err(e) => problems.push(e.message),
+        }
+    }
+
+    if !problems.is_empty() {
+        warn!("Printer {} ({}): {}", name, role, problems.join(", "));
+    }
+    PrinterHealth { role: role.to_string(), name: name.to_string(), state, problems }
+}
+
+fn spooler_problems(reasons: &[String]) -> Vec<String> {
+    reasons
+        .iter()
+        .filter(|reason| !BUSY_REASONS.contains(&reason.as_str()))
+        .cloned()
+        .collect()
+}
+
+// a Zebra on a standard TCP/IP port, which Windows names after the address ("IP_10.1.2.3", "10.1.2.3_1")
+fn zebra_address(printer: &Printer) -> Option<SocketAddr> {
+    let is_zebra = [&printer.name, &printer.driver_name]
+        .iter()
+        .any(|s| s.to_ascii_lowercase().contains("zebra") || s.to_ascii_lowercase().contains("zdesigner"));
+    if !is_zebra {
+        return None;
+    }
+    let port = printer.port_name.trim();
+    let port = port.strip_prefix("IP_").unwrap_or(port);
+    let ip: Ipv4Addr = port.split('_').next()?.parse().ok()?;
+    Some(SocketAddr::from((ip, ZEBRA_PORT)))
+}
+
+// a Zebra address from the settings: an IP or host name, with a port when it isn't 9100
+fn host_address(host: &str) -> Result<SocketAddr, AppError> {
+    let host = host.trim();
+    let with_port = if host.contains(':') { host.to_string() } else { format!("{}:{}", host, ZEBRA_PORT) };
+    with_port
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addresses| addresses.next())
+        .ok_or_else(|| AppError::printer("zebra_unreachable", format!("Can't find the printer at '{}'", host)))
+}
+
+fn query_zebra(address: SocketAddr) -> Result<HostStatus, AppError> {
+    let unreachable = |e: std::io::Error| AppError::printer("zebra_unreachable", format!("No answer from the printer at {}: {}", address, e));
+    let mut stream = TcpStream::connect_timeout(&address, ZEBRA_TIMEOUT).map_err(unreachable)?;
+    stream.set_read_timeout(Some(ZEBRA_TIMEOUT)).map_err(unreachable)?;
+    stream.write_all(HOST_STATUS).map_err(unreachable)?;
+
+    // read until the third string ends, a slow printer may send them in pieces
+    let mut response = Vec::new();
+    let mut buffer = [0u8; 256];
+    while response.iter().filter(|&&b| b == ETX as u8).count() < 3 {
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => response.extend_from_slice(&buffer[..read]),
+            Err(e) if response.is_empty() => return Err(unreachable(e)),
+            Err(_) => break,
+        }
+    }
+    let response = String::from_utf8_lossy(&response);
+    debug!("~HS from {}: {:?}", address, response);
+    parse_host_status(&response)
+}
+
+// string 1: aaa,b,c,dddd,eee,f,g,h,iii,j,k,l with b paper out, c paused, f buffer full, j corrupt RAM,
+//           k under temperature, l over temperature
+// string 2: mmm,n,o,p,... with o head open, p ribbon out
+// string 3 (password and static RAM) isn't used
+fn parse_host_status(response: &str) -> Result<HostStatus, AppError> {
+    let strings: Vec<Vec<&str>> = response
+        .split([STX, ETX])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.split(',').map(str::trim).collect())
+        .collect();
+    let (Some(first), Some(second)) = (strings.first(), strings.get(1)) else {
+        return Err(AppError::printer("zebra_status_invalid", format!("Unexpected host status from the printer: {:?}", response)));
+    };
+    if first.len() < 12 || second.len() < 4 {
+        return Err(AppError::printer("zebra_status_invalid", format!("Unexpected host status from the printer: {:?}", response)));
+    }
+
+    let flag = |field: &str| field == "1";
+    Ok(HostStatus {
+        paper_out: flag(first[1]),
+        paused: flag(first[2]),
+        buffer_full: flag(first[5]),
+        corrupt_ram: flag(first[9]),
+        under_temperature: flag(first[10]),
+        over_temperature: flag(first[11]),
+        head_open: flag(second[2]),
+        ribbon_out: flag(second[3]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host_status(first: &str, second: &str) -> String {
+        format!("\u{2}{}\u{3}\r\n\u{2}{}\u{3}\r\n\u{2}1234,0\u{3}\r\n", first, second)
+    }
+
+    #[test]
+    fn ready_zebra_has_no_problems() {
+        let status = parse_host_status(&host_status("030,0,0,1245,000,0,0,0,000,0,0,0", "001,0,0,0,1,2,6,0,00000000,1,000")).unwrap();
+
+        assert_eq!(status, HostStatus::default());
+        assert!(status.problems().is_empty());
+    }
+
+    #[test]
+    fn paused_zebra_out_of_labels() {
+        let status = parse_host_status(&host_status("030,1,1,1245,000,0,0,0,000,0,0,0", "001,0,1,0,1,2,6,0,00000000,1,000")).unwrap();
+
+        assert_eq!(status.problems(), vec!["out of labels", "paused on the printer", "print head open"]);
+    }
+
+    #[test]
+    fn short_answers_are_an_error() {
+        for response in ["", "\u{2}030,0,0\u{3}", &host_status("030,0,0,1245,000,0,0,0,000,0,0,0", "")] {
+            assert_eq!(parse_host_status(response).unwrap_err().code, "zebra_status_invalid", "{:?}", response);
+        }
+    }
+
+    #[test]
+    fn configured_addresses_default_to_the_raw_port() {
+        assert_eq!(host_address(" 10.1.2.3 ").unwrap(), SocketAddr::from(([10, 1, 2, 3], 9100)));
+        assert_eq!(host_address("10.1.2.3:6101").unwrap(), SocketAddr::from(([10, 1, 2, 3], 6101)));
+        assert_eq!(host_address("10.1.2.3:port").unwrap_err().code, "zebra_unreachable");
+    }
+
+    #[test]
+    fn busy_spooler_reasons_are_not_problems() {
+        let reasons = ["printing", "io_active", "paper_out", "door_open"].map(String::from);
+
+        assert_eq!(spooler_problems(&reasons), vec!["paper_out", "door_open"]);
+        assert!(spooler_problems(&["none".to_string()]).is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+use serde::{Serialize, Deserialize};
+use crate::context::AppContext;
+use crate::settings::write_json;
+use crate::error::AppError;
+
+// which ERP orders the app treats as printable, kept in queryConfig.json next to the settings so planners
+// can add an order series or product family without a new build. empty lists mean "any"
+const QUERY_CONFIG_FILE: &str = "queryConfig.json";
+pub const QUERY_CONFIG_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct QueryConfig {
+    pub version: u32,
+    pub open_orders: OpenOrderRules,
+    pub order_lookup: LookupRules,
+    pub print_items: LookupRules,
+}
+
+// the welcome page list
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct OpenOrderRules {
+    pub order_prefixes: Vec<String>,    // ORDNUM_10
+    pub excluded_planners: Vec<String>, // PLANID_10, 000 keeps screws and stock orders out
+    pub statuses: Vec<String>,          // STATUS_10, 3 is released
+    pub part_prefixes: Vec<String>,     // the subassembly or the order's part
+}
+
+// an order typed in or picked from the list, and its label and docs rows.
+// no status by default so old shop orders can be entered to test
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct LookupRules {
+    pub statuses: Vec<String>,
+    pub subassembly_prefixes: Vec<String>,
+}
+
+impl Default for QueryConfig {
+    fn default() -> Self {
+        QueryConfig {
+            version: QUERY_CONFIG_VERSION,
+            open_orders: OpenOrderRules::default(),
+            order_lookup: LookupRules {
+                statuses: Vec::new(),
+                subassembly_prefixes: strings(&["02A", "K02A"]),
+            },
+            print_items: LookupRules::default(),
+        }
+    }
+}
+
+impl Default for OpenOrderRules {
+    fn default() -> Self {
+        OpenOrderRules {
+            order_prefixes: strings(&["5", "7"]),
+            excluded_planners: strings(&["000"]),
+            statuses: strings(&["3"]),
+            part_prefixes: strings(&["02", "K02"]),
+        }
+    }
+}
+
+impl OpenOrderRules {
+    // the status get_orders narrows the open orders to, None for every status the rules allow. blank and "any" ask
+    // for all of them, a status the rules leave out is an error rather than an empty list
+    pub fn status_filter(&self, asked: Option<&str>) -> Result<Option<String>, AppError> {
+        match asked.map(str::trim) {
+            None | Some("") => Ok(None),
+            Some(status) if status.eq_ignore_ascii_case("any") => Ok(None),
+            Some(status) if self.statuses.is_empty() || self.statuses.iter().any(|s| s.trim() == status) => Ok(Some(status.to_string())),
+            Some(status) => Err(AppError::config("order_query_invalid", format!("Status '{}' is not one the query config counts as open ({})", status, self.statuses.join(", ")))),
+        }
+    }
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+// the rules in the app data folder, written out with the defaults the first time
+pub fn load(ctx: &AppContext) -> Result<QueryConfig, AppError> {
+    let file_path = ctx.file(QUERY_CONFIG_FILE);
+
+    if !fs::exists(&file_path).map_err(|e| AppError::io("query_config_read", format!("Can't check existence of queryConfig: {}", e)).with_path(&file_path))? {
+        write_json(&file_path, &QueryConfig::default())?;
+    }
+
+    let file = File::open(&file_path)
+        .map_err(|e| AppError::io("query_config_read", format!("Failed to open query config: {}", e)).with_path(&file_path))?;
+    let config: QueryConfig = serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| AppError::config("query_config_parse", format!("Failed to parse query config: {}", e)).with_path(&file_path))?;
+    if config.version > QUERY_CONFIG_VERSION {
+        return Err(AppError::config("query_config_version", format!("Query config version {} is newer than this app supports ({})", config.version, QUERY_CONFIG_VERSION)).with_path(&file_path));
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::temp_context;
+    use chrono::Local;
+
+    #[test]
+    fn creates_defaults_on_first_load() {
+        let ctx = temp_context("query-config-defaults", Local::now());
+
+        assert_eq!(load(&ctx).unwrap(), QueryConfig::default());
+        assert!(ctx.file(QUERY_CONFIG_FILE).exists());
+    }
+
+    #[test]
+    fn missing_rules_keep_their_defaults() {
+        let ctx = temp_context("query-config-partial", Local::now());
+        fs::write(ctx.file(QUERY_CONFIG_FILE), r#"{ "version": 1, "open_orders": { "order_prefixes": ["5", "7", "8"] } }"#).unwrap();
+
+        let config = load(&ctx).unwrap();
+        assert_eq!(config.open_orders.order_prefixes, vec!["5", "7", "8"]);
+        assert_eq!(config.open_orders.statuses, vec!["3"]);
+        assert_eq!(config.order_lookup, QueryConfig::default().order_lookup);
+    }
+
+    #[test]
+    fn status_filter_stays_within_the_rules() {
+        let open = OpenOrderRules::default();
+
+        assert_eq!(open.status_filter(None).unwrap(), None);
+        assert_eq!(open.status_filter(Some(" ANY ")).unwrap(), None);
+        assert_eq!(open.status_filter(Some(" 3 ")).unwrap().as_deref(), Some("3"));
+        assert_eq!(open.status_filter(Some("2")).unwrap_err().code, "order_query_invalid");
+        // no status rule, any status can be asked for
+        let any = OpenOrderRules { statuses: Vec::new(), ..OpenOrderRules::default() };
+        assert_eq!(any.status_filter(Some("2")).unwrap().as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let ctx = temp_context("query-config-newer", Local::now());
+        fs::write(ctx.file(QUERY_CONFIG_FILE), r#"{ "version": 99 }"#).unwrap();
+
+        assert_eq!(load(&ctx).unwrap_err().code, "query_config_version");
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt;
+use crate::check_digit::CheckDigit;
+use crate::error::AppError;
+
+// a serial number as kept in SerialNumberCount.txt and typed into the window, digits zero padded to a fixed width.
+// the leading digits are the year and week (see serial_number_files::reset_serial), the last four count units
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SerialNumber {
+    value: u64,
+    width: usize,
+}
+
+// every 19 digit number fits in a u64
+const MAX_WIDTH: usize = 19;
+
+impl SerialNumber {
+    // surrounding whitespace and newlines are dropped, the leading zeros set the width
+    pub fn parse(raw: &str) -> Result<Self, AppError> {
+        let digits = raw.trim();
+        let invalid = || AppError::serial("serial_invalid", format!("'{}' is not a serial number, expected 1 to {} digits", digits, MAX_WIDTH));
+        if digits.is_empty() || digits.len() > MAX_WIDTH || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let value = digits.parse().map_err(|_| invalid())?;
+        Ok(SerialNumber { value, width: digits.len() })
+    }
+
+    // value zero padded to width, an error when it needs more digits than that
+    pub fn new(value: u64, width: usize) -> Result<Self, AppError> {
+        let width = width.clamp(1, MAX_WIDTH);
+        if digits(value) > width {
+            return Err(AppError::serial("serial_overflow", format!("{} doesn't fit a {} digit serial number", value, width)));
+        }
+        Ok(SerialNumber { value, width })
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    // as it goes on a label or final doc, with the check character from the settings
+    pub fn printed(&self, check_digit: CheckDigit) -> String {
+        check_digit.append(&self.to_string())
+    }
+
+    // the serial count units further on, in the same width
+    pub fn checked_add(&self, count: u32) -> Result<Self, AppError> {
+        self.value
+            .checked_add(u64::from(count))
+            .filter(|value| digits(*value) <= self.width)
+            .map(|value| SerialNumber { value, width: self.width })
+            .ok_or_else(|| AppError::serial("serial_overflow", format!("Serial {} + {} runs past {} digits", self, count, self.width)))
+    }
+
+    // count serials starting with this one, checked up front so a print never stops half way
+    pub fn range(&self, count: u32) -> Result<impl Iterator<Item = SerialNumber>, AppError> {
+        if count > 0 {
+            self.checked_add(count - 1)?;
+        }
+        let first = *self;
+        Ok((0..u64::from(count)).map(move |i| SerialNumber { value: first.value + i, width: first.width }))
+    }
+}
+
+impl fmt::Display for SerialNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:0width$}", self.value, width = self.width)
+    }
+}
+
+fn digits(value: u64) -> usize {
+    value.checked_ilog10().map_or(1, |d| d as usize + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serial(raw: &str) -> SerialNumber {
+        SerialNumber::parse(raw).unwrap()
+    }
+
+    #[test]
+    fn parse_trims_and_keeps_leading_zeros() {
+        assert_eq!(serial("001010129").to_string(), "001010129");
+        assert_eq!(serial(" 001010129\r\n").to_string(), "001010129");
+        assert_eq!(serial("0").width(), 1);
+        assert_eq!(serial("0000000000000000042").value(), 42);
+
+        for raw in ["", "  ", "00101O129", "-1", "1.5", "12 34", "00000000000000000001"] {
+            assert_eq!(SerialNumber::parse(raw).unwrap_err().code, "serial_invalid", "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn add_stays_in_width() {
+        assert_eq!(serial("001010129").checked_add(10).unwrap().to_string(), "001010139");
+        assert_eq!(serial("009999999").checked_add(1).unwrap().to_string(), "010000000");
+        assert_eq!(serial("999999999").checked_add(1).unwrap_err().code, "serial_overflow");
+        assert_eq!(serial("9999999999999999999").checked_add(1).unwrap_err().code, "serial_overflow");
+    }
+
+    #[test]
+    fn range_counts_from_the_first_serial() {
+        let serials: Vec<String> = serial("001010198").range(3).unwrap().map(|s| s.to_string()).collect();
+        assert_eq!(serials, vec!["001010198", "001010199", "001010200"]);
+
+        assert_eq!(serial("001010198").range(0).unwrap().count(), 0);
+        assert_eq!(serial("998").range(2).unwrap().count(), 2);
+        assert!(serial("998").range(3).is_err());
+    }
+}
@@ -1,28 +1,35 @@
 use std::path::PathBuf;
-use std::io::{prelude::*, Write};
-use std::env;
+use std::io::{prelude::*, BufReader, Write};
 use std::fs::{self, OpenOptions, File};
-use tauri::{AppHandle, Manager, path::BaseDirectory};
-use chrono::{prelude::*, Datelike, Utc};
+use serde::Serialize;
+use tauri::AppHandle;
+use chrono::{prelude::*, Datelike};
+use crate::context::AppContext;
+use crate::check_digit::CheckDigit;
+use crate::serial_number::SerialNumber;
+use crate::error::AppError;
 
-pub fn serial_number_tracker(part_number: String, assn_number: String, serial_number: String, user: String, app_handle: &AppHandle) -> Result<(), String>{
+const TRACKER_FILE: &str = "serialNumberTracker.txt";
+const SERIAL_COUNT_FILE: &str = "SerialNumberCount.txt";
+const LAST_RESET_WEEK_FILE: &str = "last_reset_week.txt";
+
+// one printed unit as written to serialNumberTracker.txt
+#[derive(Serialize, Debug)]
+pub struct TrackerEntry {
+    pub date: String,
+    pub part_number: String,
+    pub assn_number: String,
+    pub serial_number: String,
+    pub user: String,
+}
+
+pub fn serial_number_tracker(part_number: String, assn_number: String, serial_number: String, user: String, ctx: &AppContext) -> Result<(), AppError>{
     // check if file exists and create it 
-    let doc_path;
-    let file_path;
-    // if the DOC_PATH is build,  we navigate AppData, else go to the .env location
-    if env!("DOC_PATH") == "build" {
-        file_path = app_handle
-            .path()
-            .resolve("serialNumberTracker.txt", BaseDirectory::AppData)
-            .map_err(|e| format!("Failed to resolve settings path: {}", e))?;
-    } else {
-        doc_path = env!("DOC_PATH");
-        file_path = PathBuf::from(doc_path).join("serialNumberTracker.txt");
-    }
-    if !fs::exists(&file_path).expect("Can't check existence of serialNumberTracker") {
+    let file_path = ctx.file(TRACKER_FILE);
+    if !fs::exists(&file_path).map_err(|e| AppError::io("tracker_read", format!("Can't check existence of serialNumberTracker: {}", e)).with_path(&file_path))? {
         match create_serial_number_tracker(&file_path) {
             Ok(_) => (),
-            Err(e) => return Err(e.to_string()),
+            Err(e) => return Err(AppError::io("tracker_write", e.to_string()).with_path(&file_path)),
          }
     }
 
@@ -32,19 +39,71 @@ pub fn serial_number_tracker(part_number: String, assn_number: String, serial_nu
         .append(true)
         .open(&file_path);
 
-    let local: DateTime<Local> = Local::now();
+    let local: DateTime<Local> = ctx.now();
     let padded_date = format!("{: <12}", local.format("%Y-%m-%d"));
     let padded_part_number = format!("{: <30}", part_number);
     let padded_assn_number = format!("{: <30}", assn_number);
     let padded_serial_number = format!("{: <16}", serial_number);
 
-    if let Err(e) = writeln!(file.map_err(|e| format!("Failed to write to serial number tracker: {}", e))?, "{}{}{}{}{}", padded_date,padded_part_number,padded_assn_number,padded_serial_number,user) {
-        eprintln!("Couldn't write to file: {}", e);
+    if let Err(e) = writeln!(file.map_err(|e| AppError::io("tracker_write", format!("Failed to write to serial number tracker: {}", e)).with_path(&file_path))?, "{}{}{}{}{}", padded_date,padded_part_number,padded_assn_number,padded_serial_number,user) {
+        log::error!("Couldn't write to file: {}", e);
         // return Err(format!("Couldn't write to file: {}", e));
     }
     Ok(())
 }
 
+// every tracker line printed with serial_number, oldest first. the tracker keeps serials without their check character,
+// so a scanned serial has to carry a valid one when the settings add them, unless it was printed before they did
+pub fn lookup_serial(serial_number: &str, ctx: &AppContext) -> Result<Vec<TrackerEntry>, AppError> {
+    let check_digit = ctx.settings()?.check_digit;
+    let stripped = check_digit.strip(serial_number);
+    if let Ok(serial) = &stripped {
+        let entries = tracker_entries(serial, ctx)?;
+        if !entries.is_empty() || check_digit == CheckDigit::None {
+            return Ok(entries);
+        }
+    }
+
+    // serials printed before the check character was switched on don't carry one
+    let entries = tracker_entries(serial_number.trim(), ctx)?;
+    match stripped {
+        Err(e) if entries.is_empty() => Err(e),
+        _ => Ok(entries),
+    }
+}
+
+fn tracker_entries(serial_number: &str, ctx: &AppContext) -> Result<Vec<TrackerEntry>, AppError> {
+    let file_path = ctx.file(TRACKER_FILE);
+    if !fs::exists(&file_path).map_err(|e| AppError::io("tracker_read", format!("Can't check existence of serialNumberTracker: {}", e)).with_path(&file_path))? {
+        return Ok(Vec::new());
+    }
+
+    let file = File::open(&file_path)
+        .map_err(|e| AppError::io("tracker_read", format!("Failed to open serial number tracker: {}", e)).with_path(&file_path))?;
+
+    let mut entries = Vec::new();
+    // skip the header, columns are padded to 12, 30, 30 and 16 characters
+    for line in BufReader::new(file).lines().skip(1) {
+        let line = line.map_err(|e| AppError::io("tracker_read", format!("Failed to read serial number tracker: {}", e)).with_path(&file_path))?;
+        let column = |from: usize, to: Option<usize>| {
+            let end = to.unwrap_or(line.len()).min(line.len());
+            line.get(from.min(end)..end).unwrap_or("").trim().to_string()
+        };
+        let entry = TrackerEntry {
+            date: column(0, Some(12)),
+            part_number: column(12, Some(42)),
+            assn_number: column(42, Some(72)),
+            serial_number: column(72, Some(88)),
+            user: column(88, None),
+        };
+        if entry.serial_number == serial_number {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
 fn create_serial_number_tracker(path: &PathBuf) -> Result<(), std::io::Error>{
     let mut file = File::create(path)?;
     let header = "Date        Model Number                  Part Number                   Serial Number   Initials \n";
@@ -52,33 +111,19 @@ fn create_serial_number_tracker(path: &PathBuf) -> Result<(), std::io::Error>{
     Ok(())
 }
 
-pub async fn serial_number_up(serial_number: String,  app_handle: &AppHandle) -> Result<(), String> {
-    let doc_path;
-    let file_path;
-    // if the DOC_PATH is build,  we navigate AppData, else go to the .env location
-    if env!("DOC_PATH") == "build" {
-        file_path = app_handle
-            .path()
-            .resolve("SerialNumberCount.txt", BaseDirectory::AppData)
-            .map_err(|e| format!("Failed to resolve SerialNumberCount path: {}", e))?;
-    } else {
-        doc_path = env!("DOC_PATH");
-        file_path = PathBuf::from(doc_path).join("SerialNumberCount.txt");
-    }
+pub async fn serial_number_up(serial_number: SerialNumber, ctx: &AppContext) -> Result<(), AppError> {
+    let file_path = ctx.file(SERIAL_COUNT_FILE);
     let file_serial_number;
-    match internal_get_serial_number(&app_handle).await {
+    match internal_get_serial_number(ctx).await {
         Ok(v) =>  file_serial_number = v,
-        Err(e) => return Err(format!("Error reading file serial number: {}", e)),
+        Err(e) => return Err(AppError { message: format!("Error reading file serial number: {}", e.message), ..e }.with_path(&file_path)),
     }
-    let fsnn = file_serial_number.parse::<i32>().unwrap();
-    let snn = serial_number.parse::<i32>().unwrap();
-    if snn > fsnn {
-        let width = serial_number.len();
-        let new_serial = format!("{:0width$}", snn, width = width);
+    if serial_number.value() > file_serial_number.value() {
+        let new_serial = serial_number.to_string();
 
-        let mut file = File::create(&file_path).map_err(|e| format!("Failed to create SerialNumberCount: {}", e))?;
+        let mut file = File::create(&file_path).map_err(|e| AppError::io("serial_write", format!("Failed to create SerialNumberCount: {}", e)).with_path(&file_path))?;
         
-        file.write_all(new_serial.as_bytes()).map_err(|e| format!("Failed to write to SerialNumberCount: {}", e))?;
+        file.write_all(new_serial.as_bytes()).map_err(|e| AppError::io("serial_write", format!("Failed to write to SerialNumberCount: {}", e)).with_path(&file_path))?;
     } 
     Ok(())
 }
@@ -91,140 +136,111 @@ fn create_serial_number_count(path: &PathBuf) -> Result<(), std::io::Error> {
 }
 
 #[tauri::command]
-pub async fn get_serial_number(app_handle: AppHandle) -> Result<String, String> {
-    return internal_get_serial_number(&app_handle).await;
+pub async fn get_serial_number(app_handle: AppHandle) -> Result<String, AppError> {
+    return internal_get_serial_number(&AppContext::from_app(&app_handle)?).await.map(|serial| serial.to_string());
 }
 
-async fn internal_get_serial_number(app_handle: &AppHandle) -> Result<String, String> {
-    let doc_path;
-    let file_path;
-    // if the DOC_PATH is build,  we navigate AppData, else go to the .env location
-    if env!("DOC_PATH") == "build" {
-        file_path = app_handle
-            .path()
-            .resolve("SerialNumberCount.txt", BaseDirectory::AppData)
-            .map_err(|e| format!("Failed to resolve SerialNumberCount path: {}", e))?;
-
-
-    } else {
-        doc_path = env!("DOC_PATH");
-        file_path = PathBuf::from(doc_path).join("SerialNumberCount.txt");
-    }
-    if !fs::exists(&file_path).expect("Can't check existence of serialNumberCount") {
+pub async fn internal_get_serial_number(ctx: &AppContext) -> Result<SerialNumber, AppError> {
+    let file_path = ctx.file(SERIAL_COUNT_FILE);
+    if !fs::exists(&file_path).map_err(|e| AppError::io("serial_read", format!("Can't check existence of serialNumberCount: {}", e)).with_path(&file_path))? {
         match create_serial_number_count(&file_path) {
             Ok(_) => (),
-            Err(e) => return Err(e.to_string()),
+            Err(e) => return Err(AppError::io("serial_write", e.to_string()).with_path(&file_path)),
          }
     }
     
-    let mut serial_number_file = File::open(file_path)
-        .map_err(|_| "Failed to open serial number file")?;
+    let mut serial_number_file = File::open(&file_path)
+        .map_err(|e| AppError::io("serial_read", format!("Failed to open serial number file: {}", e)).with_path(&file_path))?;
 
     let mut file_serial_number = String::new();
     
     serial_number_file
         .read_to_string(&mut file_serial_number)
-        .map_err(|_| "Failed to read serial number file")?;
+        .map_err(|e| AppError::io("serial_read", format!("Failed to read serial number file: {}", e)).with_path(&file_path))?;
 
-    Ok(file_serial_number)
+    SerialNumber::parse(&file_serial_number).map_err(|e| e.with_path(&file_path))
 }
 
-const LAST_RESET_WEEK_FILE: &str = "last_reset_week.txt";
 
 #[tauri::command]
-pub async fn reset_serial_check(app_handle: AppHandle) -> Result<(), String> {
-    return handle_serial(&app_handle).await;
+pub async fn reset_serial_check(app_handle: AppHandle) -> Result<(), AppError> {
+    return handle_serial(&AppContext::from_app(&app_handle)?).await;
 }
 
-async fn handle_serial(app_handle: &AppHandle)  -> Result<(), String> {
+pub async fn handle_serial(ctx: &AppContext)  -> Result<(), AppError> {
     let serial;
-    match internal_get_serial_number(&app_handle).await {
+    match internal_get_serial_number(ctx).await {
         Ok(v) =>  serial = v,
         Err(e) => return Err(e),
     }
 
-    let now = Utc::now();
-    let current_week = format!("{}-{:02}", &now.iso_week().year(), &now.iso_week().week()); // 2025-36
-
-    let last_reset_week = get_weekly_reset_date(app_handle)
-        .map_err(|e| format!("Failed to get weekly reset date: {}", e))?;
+    let last_reset_week = get_weekly_reset_date(ctx)
+        .map_err(|e| AppError::serial("reset_week_read", format!("Failed to get weekly reset date: {}", e.message)))?;
 
-
-    if current_week != last_reset_week {
+    if let Some((new_serial, current_week)) = reset_serial(serial, &last_reset_week, ctx.now())? {
         // reset serial_number
-        let doc_path = env!("DOC_PATH");
-        let file_path;
-        // if the DOC_PATH is build,  we navigate AppData, else go to the .env location
-        if doc_path == "build" {
-            file_path = app_handle
-                .path()
-                .resolve("SerialNumberCount.txt", BaseDirectory::AppData)
-                .map_err(|e| format!("Failed to resolve SerialNumberCount path: {}", e))?;
-        } else {
-            file_path = PathBuf::from(doc_path).join("SerialNumberCount.txt");
-        }
-        // CHECK IF WEEKLY OR IF YEARLY
-        let mut sn = serial.parse().unwrap_or(0);
-        let sn_string;
-        if current_week.chars().nth(3) == last_reset_week.chars().nth(3) { // weekly
-            sn = (sn/10000)+1;
-            sn_string = format!("{}0101", sn);
-        } else { // yearly
-            sn = (sn/1000000)+1;
-            sn_string = format!("{}010101", sn);
-        }
-        let width = serial.len();
-        let new_serial = format!("{:0>width$}", sn_string, width = width);
-
-        let mut file = File::create(&file_path).map_err(|e| format!("Failed to create SerialNumberCount: {}", e))?;  
-        file.write_all(new_serial.as_bytes()).map_err(|e| format!("Failed to write to SerialNumberCount: {}", e))?;
+        let file_path = ctx.file(SERIAL_COUNT_FILE);
+        let mut file = File::create(&file_path).map_err(|e| AppError::io("serial_write", format!("Failed to create SerialNumberCount: {}", e)).with_path(&file_path))?;  
+        file.write_all(new_serial.to_string().as_bytes()).map_err(|e| AppError::io("serial_write", format!("Failed to write to SerialNumberCount: {}", e)).with_path(&file_path))?;
 
         // now reset week value
-        let file_path_time;
-        // if the DOC_PATH is build,  we navigate AppData, else go to the .env location
-        if doc_path == "build" {
-            file_path_time = app_handle
-                .path()
-                .resolve(LAST_RESET_WEEK_FILE, BaseDirectory::AppData)
-                .map_err(|e| format!("Failed to resolve LAST_RESET_WEEK_FILE path: {}", e))?;
-        } else {
-            file_path_time = PathBuf::from(doc_path).join(LAST_RESET_WEEK_FILE);
-        }
+        let file_path_time = ctx.file(LAST_RESET_WEEK_FILE);
 
-        let mut file_time = File::create(&file_path_time).map_err(|e| format!("Failed to create LAST_RESET_WEEK_FILE: {}", e))?;  
-        file_time.write_all(current_week.as_bytes()).map_err(|e| format!("Failed to write to LAST_RESET_WEEK_FILE: {}", e))?;
+        let mut file_time = File::create(&file_path_time).map_err(|e| AppError::io("reset_week_write", format!("Failed to create LAST_RESET_WEEK_FILE: {}", e)).with_path(&file_path_time))?;  
+        file_time.write_all(current_week.as_bytes()).map_err(|e| AppError::io("reset_week_write", format!("Failed to write to LAST_RESET_WEEK_FILE: {}", e)).with_path(&file_path_time))?;
     }
 
     Ok(())
 }
 
-fn get_weekly_reset_date(app_handle: &AppHandle) -> Result<String, String> {
-    let doc_path = env!("DOC_PATH");
-    let file_path_week;
-    // if the DOC_PATH is build,  we navigate AppData, else go to the .env location
-    if env!("DOC_PATH") == "build" {
-        file_path_week = app_handle
-            .path()
-            .resolve(LAST_RESET_WEEK_FILE, BaseDirectory::AppData)
-            .map_err(|e| format!("Failed to resolve last_reset_week path: {}", e))?;
-    } else {
-        file_path_week = PathBuf::from(doc_path).join(LAST_RESET_WEEK_FILE);
+// the week a reset belongs to, e.g. 2025-36, on the shop's local calendar
+fn reset_week(now: DateTime<Local>) -> String {
+    format!("{}-{:02}", now.iso_week().year(), now.iso_week().week())
+}
+
+// serial number and reset week after a reset check, None while still in the last reset week.
+// a new ISO year restarts the week digits, any other new week moves them on by one however many weeks were missed
+fn reset_serial(serial: SerialNumber, last_reset_week: &str, now: DateTime<Local>) -> Result<Option<(SerialNumber, String)>, AppError> {
+    let current_week = reset_week(now);
+    let last_reset_week = last_reset_week.trim();
+    if current_week == last_reset_week {
+        return Ok(None);
     }
-    if !fs::exists(&file_path_week).expect("Can't check existence of last_reset_week") {
+
+    let last_year = last_reset_week
+        .split_once('-')
+        .and_then(|(year, _)| year.parse::<i32>().ok())
+        .ok_or_else(|| AppError::serial("reset_week_invalid", format!("Last reset week '{}' is not a year-week like 2025-36", last_reset_week)))?;
+
+    // CHECK IF WEEKLY OR IF YEARLY
+    let sn = serial.value();
+    let new_value = if now.iso_week().year() == last_year { // weekly, ...WW0101
+        (sn / 10000 + 1) * 10000 + 101
+    } else { // yearly, ...01010101
+        (sn / 1000000 + 1) * 1000000 + 10101
+    };
+    let new_serial = SerialNumber::new(new_value, serial.width())?;
+
+    Ok(Some((new_serial, current_week)))
+}
+
+fn get_weekly_reset_date(ctx: &AppContext) -> Result<String, AppError> {
+    let file_path_week = ctx.file(LAST_RESET_WEEK_FILE);
+    if !fs::exists(&file_path_week).map_err(|e| AppError::io("reset_week_read", format!("Can't check existence of last_reset_week: {}", e)).with_path(&file_path_week))? {
         match create_last_reset_week(&file_path_week) {
             Ok(_) => (),
-            Err(e) => return Err(e.to_string()),
+            Err(e) => return Err(AppError::io("reset_week_write", e.to_string()).with_path(&file_path_week)),
         }
     }
     
-    let mut serial_number_file = File::open(file_path_week)
-        .map_err(|_| "Failed to open last_reset_week file")?;
+    let mut serial_number_file = File::open(&file_path_week)
+        .map_err(|e| AppError::io("reset_week_read", format!("Failed to open last_reset_week file: {}", e)).with_path(&file_path_week))?;
 
     let mut last_reset_week = String::new();
     
     serial_number_file
         .read_to_string(&mut last_reset_week)
-        .map_err(|_| "Failed to read last_reset_week file")?;
+        .map_err(|e| AppError::io("reset_week_read", format!("Failed to read last_reset_week file: {}", e)).with_path(&file_path_week))?;
 
     Ok(last_reset_week)
 }
@@ -234,4 +250,173 @@ fn create_last_reset_week(path: &PathBuf) -> Result<(), std::io::Error> {
     let start = "2021-01";
     file.write_all(start.as_bytes())?;
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::temp_context;
+    use crate::settings::save_settings_in;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap()
+    }
+
+    fn write(ctx: &AppContext, file_name: &str, contents: &str) {
+        fs::write(ctx.file(file_name), contents).unwrap();
+    }
+
+    fn read(ctx: &AppContext, file_name: &str) -> String {
+        fs::read_to_string(ctx.file(file_name)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn creates_serial_files_on_first_use() {
+        let ctx = temp_context("serial-first-use", at(2025, 9, 3));
+
+        assert_eq!(internal_get_serial_number(&ctx).await.unwrap().to_string(), "001010129");
+        assert_eq!(get_weekly_reset_date(&ctx).unwrap(), "2021-01");
+    }
+
+    #[tokio::test]
+    async fn same_week_keeps_serial() {
+        let ctx = temp_context("serial-same-week", at(2025, 9, 3));
+        write(&ctx, SERIAL_COUNT_FILE, "001360555");
+        write(&ctx, LAST_RESET_WEEK_FILE, "2025-36");
+
+        handle_serial(&ctx).await.unwrap();
+
+        assert_eq!(read(&ctx, SERIAL_COUNT_FILE), "001360555");
+        assert_eq!(read(&ctx, LAST_RESET_WEEK_FILE), "2025-36");
+    }
+
+    #[tokio::test]
+    async fn new_week_bumps_week_digits() {
+        let ctx = temp_context("serial-new-week", at(2025, 9, 3));
+        write(&ctx, SERIAL_COUNT_FILE, "001360555");
+        write(&ctx, LAST_RESET_WEEK_FILE, "2025-35");
+
+        handle_serial(&ctx).await.unwrap();
+
+        assert_eq!(read(&ctx, SERIAL_COUNT_FILE), "001370101");
+        assert_eq!(read(&ctx, LAST_RESET_WEEK_FILE), "2025-36");
+    }
+
+    #[tokio::test]
+    async fn new_year_bumps_year_digits() {
+        let ctx = temp_context("serial-new-year", at(2025, 1, 8));
+        write(&ctx, SERIAL_COUNT_FILE, "001520342");
+        write(&ctx, LAST_RESET_WEEK_FILE, "2024-52");
+
+        handle_serial(&ctx).await.unwrap();
+
+        assert_eq!(read(&ctx, SERIAL_COUNT_FILE), "002010101");
+        assert_eq!(read(&ctx, LAST_RESET_WEEK_FILE), "2025-02");
+    }
+
+    #[test]
+    fn reset_transitions() {
+        // (case, serial, last reset week, today, expected serial and week or None for no reset)
+        let cases: &[(&str, &str, &str, DateTime<Local>, Option<(&str, &str)>)] = &[
+            ("same week", "001360555", "2025-36", at(2025, 9, 3), None),
+            ("same week, sunday", "001360555", "2025-36", at(2025, 9, 7), None),
+            ("next week", "001360555", "2025-36", at(2025, 9, 8), Some(("001370101", "2025-37"))),
+            ("missed weeks move one step", "001150555", "2025-30", at(2025, 9, 3), Some(("001160101", "2025-36"))),
+            ("new year", "001520342", "2024-52", at(2025, 1, 8), Some(("002010101", "2025-02"))),
+            ("iso year starts in december", "001520342", "2024-52", at(2024, 12, 30), Some(("002010101", "2025-01"))),
+            ("decade boundary", "009520342", "2029-52", at(2030, 1, 2), Some(("010010101", "2030-01"))),
+            ("ten years apart, same last digit", "001100342", "2025-10", at(2035, 3, 7), Some(("002010101", "2035-10"))),
+            ("week 53", "006520342", "2026-52", at(2026, 12, 28), Some(("006530101", "2026-53"))),
+            ("january 1st still in week 53", "006520342", "2026-52", at(2027, 1, 1), Some(("006530101", "2026-53"))),
+            ("out of week 53", "006530342", "2026-53", at(2027, 1, 4), Some(("007010101", "2027-01"))),
+            ("missed a whole year", "001400342", "2019-40", at(2021, 1, 1), Some(("002010101", "2020-53"))),
+            ("missed the year change", "001480342", "2024-48", at(2025, 2, 12), Some(("002010101", "2025-07"))),
+            ("whitespace in files", " 001360555\n", "2025-35\r\n", at(2025, 9, 3), Some(("001370101", "2025-36"))),
+        ];
+
+        for (case, serial, last_week, now, expected) in cases {
+            let got = reset_serial(SerialNumber::parse(serial).unwrap(), last_week, *now).unwrap().map(|(s, w)| (s.to_string(), w));
+            let expected = expected.map(|(s, w)| (s.to_string(), w.to_string()));
+            assert_eq!(got, expected, "{}", case);
+        }
+    }
+
+    #[test]
+    fn unreadable_reset_week_is_an_error() {
+        let e = reset_serial(SerialNumber::parse("001360555").unwrap(), "week 36", at(2025, 9, 3)).unwrap_err();
+        assert_eq!(e.code, "reset_week_invalid");
+    }
+
+    #[tokio::test]
+    async fn serial_only_moves_forward() {
+        let ctx = temp_context("serial-up", at(2025, 9, 3));
+        write(&ctx, SERIAL_COUNT_FILE, "001360555");
+
+        serial_number_up(SerialNumber::parse("001360550").unwrap(), &ctx).await.unwrap();
+        assert_eq!(read(&ctx, SERIAL_COUNT_FILE), "001360555");
+
+        serial_number_up(SerialNumber::parse("001360560").unwrap(), &ctx).await.unwrap();
+        assert_eq!(read(&ctx, SERIAL_COUNT_FILE), "001360560");
+    }
+
+    #[tokio::test]
+    async fn unreadable_counter_is_an_error() {
+        let ctx = temp_context("serial-unreadable", at(2025, 9, 3));
+        write(&ctx, SERIAL_COUNT_FILE, "00136O555");
+        write(&ctx, LAST_RESET_WEEK_FILE, "2025-35");
+
+        assert_eq!(internal_get_serial_number(&ctx).await.unwrap_err().code, "serial_invalid");
+        assert_eq!(handle_serial(&ctx).await.unwrap_err().code, "serial_invalid");
+        assert_eq!(serial_number_up(SerialNumber::parse("001360560").unwrap(), &ctx).await.unwrap_err().code, "serial_invalid");
+        assert_eq!(read(&ctx, SERIAL_COUNT_FILE), "00136O555");
+    }
+
+    #[test]
+    fn tracker_writes_header_and_padded_rows() {
+        let ctx = temp_context("tracker", at(2025, 9, 3));
+
+        serial_number_tracker("02A000123".to_string(), "02A000456".to_string(), "001360555".to_string(), "JS".to_string(), &ctx).unwrap();
+        serial_number_tracker("02A000123".to_string(), "02A000456".to_string(), "001360556".to_string(), "JS".to_string(), &ctx).unwrap();
+
+        let contents = read(&ctx, TRACKER_FILE);
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Date        Model Number"));
+        assert_eq!(lines[1], format!("{: <12}{: <30}{: <30}{: <16}JS", "2025-09-03", "02A000123", "02A000456", "001360555"));
+
+        let found = lookup_serial("001360556", &ctx).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].part_number, "02A000123");
+        assert_eq!(found[0].assn_number, "02A000456");
+        assert_eq!(found[0].user, "JS");
+        assert!(lookup_serial("001360557", &ctx).unwrap().is_empty());
+    }
+
+    #[test]
+    fn lookup_validates_check_digits() {
+        let ctx = temp_context("tracker-check-digit", at(2025, 9, 3));
+        let mut settings = ctx.settings().unwrap();
+        settings.check_digit = CheckDigit::Luhn;
+        save_settings_in(&settings, &ctx).unwrap();
+        serial_number_tracker("02A000123".to_string(), "02A000456".to_string(), "001360555".to_string(), "JS".to_string(), &ctx).unwrap();
+
+        assert_eq!(lookup_serial("0013605555\r\n", &ctx).unwrap().len(), 1);
+        assert_eq!(lookup_serial("0013605554", &ctx).unwrap_err().code, "serial_check_digit");
+    }
+
+    #[test]
+    fn serials_printed_before_check_digits_are_still_found() {
+        let ctx = temp_context("tracker-before-check-digit", at(2025, 9, 3));
+        serial_number_tracker("02A000123".to_string(), "02A000456".to_string(), "001360555".to_string(), "JS".to_string(), &ctx).unwrap();
+        serial_number_tracker("02A000123".to_string(), "02A000456".to_string(), "001360551".to_string(), "JS".to_string(), &ctx).unwrap();
+        let mut settings = ctx.settings().unwrap();
+        settings.check_digit = CheckDigit::Luhn;
+        save_settings_in(&settings, &ctx).unwrap();
+
+        // 001360555 has no valid Luhn character on the end, 001360551 reads as 00136055 with a valid one
+        assert_eq!(lookup_serial("001360555\r\n", &ctx).unwrap()[0].serial_number, "001360555");
+        assert_eq!(lookup_serial("001360551", &ctx).unwrap()[0].serial_number, "001360551");
+        assert_eq!(lookup_serial("001360557", &ctx).unwrap_err().code, "serial_check_digit");
+    }
+}
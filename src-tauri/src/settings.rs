@@ -1,14 +1,23 @@
 use serde::{Serialize, Deserialize};
 use serde_json::Value;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::io::prelude::*;
 use std::io::BufReader;
-use std::env;
 use std::fs::{self, File};
-use tauri::{AppHandle, Manager, Emitter, path::BaseDirectory};
+use log::LevelFilter;
+use printers::{get_default_printer, get_printer_by_name};
+use tauri::{AppHandle, Emitter};
+use crate::check_digit::CheckDigit;
+use crate::context::AppContext;
+use crate::logging;
+use crate::error::AppError;
 
+const SETTINGS_FILE: &str = "appSettings.json";
+const PROFILES_FILE: &str = "settingsProfiles.json";
+const BUNDLE_VERSION: u32 = 1;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Settings {
   font_size: i32,
   dark_mode: bool,
@@ -25,77 +34,235 @@ pub struct Settings {
   pub label_printer_075_025: String,
   pub label_printer_2_3: String,
   pub label_printer_4_6: String,
+  #[serde(default = "default_log_level")]
+  pub log_level: LevelFilter,
+  #[serde(default = "default_drive_map")]
+  pub drive_map: Vec<DriveMapping>,
+  #[serde(default)]
+  pub supervisors: Vec<String>, // initials allowed to print past an order's due quantity
+  #[serde(default)]
+  pub fractional_quantity: FractionalQuantity,
+  #[serde(default)]
+  pub check_digit: CheckDigit, // appended to serials on labels and final docs
+  #[serde(default)]
+  pub archive_path: String, // pdf copies of printed reports, one folder per order. empty turns archiving off
+  #[serde(default)]
+  pub fallback_printer: String, // used when Windows has no default printer
+  #[serde(default)]
+  pub zebra_hosts: BTreeMap<String, String>, // printer setting -> address of the Zebra behind the share, for ~HS status
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DriveMapping {
+    pub drive: String,
+    pub unc: String,
+}
+
+// what to do with a due quantity like 2.5, orders in a non-integer unit of measure
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FractionalQuantity {
+    #[default]
+    Reject,    // the order has to be fixed in the ERP before it can be labeled
+    RoundUp,   // the part unit gets a label of its own
+    RoundDown,
+}
+
+#[derive(Serialize)]
+pub struct ResolvedPath {
+    pub input: String,
+    pub resolved: String,
+    pub drive: Option<String>,
+    pub exists: bool,
+}
+
+fn default_log_level() -> LevelFilter {
+    LevelFilter::Info
+}
+
+fn default_drive_map() -> Vec<DriveMapping> {
+    [
+        ("P:", "\\\\pxsvsfs01\\Production"),
+        ("Q:", "\\\\pxsvsfs01\\Quality"),
+        ("R:", "\\\\pxsvsfs01\\Purchasing"),
+        ("S:", "\\\\pxsvsfs01\\Sales & Marketing"),
+        ("X:", "\\\\pxsvsfs01\\UserData"),
+        ("Y:", "\\\\pxsvsfs01\\Engineering"),
+    ]
+    .iter()
+    .map(|(drive, unc)| DriveMapping { drive: drive.to_string(), unc: unc.to_string() })
+    .collect()
+}
+
+impl Settings {
+    // swaps a mapped drive letter for its share, paths on unmapped drives come back unchanged
+    pub fn resolve_path(&self, path: &str) -> String {
+        self.drive_mapping_for(path)
+            .map(|m| format!("{}{}", m.unc.trim_end_matches(['\\', '/']), &path.trim_start()[2..]))
+            .unwrap_or_else(|| path.to_string())
+    }
+
+    // the same settings with every configured folder and report path run through the drive map
+    pub fn with_resolved_paths(mut self) -> Self {
+        self.bom_path = self.resolve_path(&self.bom_path);
+        self.snl_path = self.resolve_path(&self.snl_path);
+        self.config_path = self.resolve_path(&self.config_path);
+        self.label_path = self.resolve_path(&self.label_path);
+        self.archive_path = self.resolve_path(&self.archive_path);
+        self
+    }
+
+    // label printer set up for a 94A label stock, None when the label size has no printer
+    pub fn label_printer(&self, print_type: &str) -> Option<&str> {
+        let printer = match print_type.trim() {
+            "94A000003-A01" => &self.label_printer_2_025,
+            "94A000004-A01" => &self.label_printer_075_025,
+            "94A000005-A01" => &self.label_printer_2_3,
+            "94A000006-A01" => &self.label_printer_125_025,
+            "94A000047-A01" => &self.label_printer_4_6,
+            _ => return None,
+        };
+        Some(printer.as_str()).filter(|p| !p.trim().is_empty())
+    }
+
+    // every printer named in the settings, with the setting that names it
+    pub fn printers(&self) -> [(&'static str, &str); 6] {
+        [
+            ("clr_printer", &self.clr_printer),
+            ("label_printer_125_025", &self.label_printer_125_025),
+            ("label_printer_2_025", &self.label_printer_2_025),
+            ("label_printer_075_025", &self.label_printer_075_025),
+            ("label_printer_2_3", &self.label_printer_2_3),
+            ("label_printer_4_6", &self.label_printer_4_6),
+        ]
+    }
+
+    // address of the Zebra behind a printer named in the settings, set for printers shared from a print server
+    // where the port doesn't give the address away
+    pub fn zebra_host(&self, printer: &str) -> Option<&str> {
+        self.printers()
+            .into_iter()
+            .find(|(_, name)| !name.trim().is_empty() && name.trim().eq_ignore_ascii_case(printer.trim()))
+            .and_then(|(setting, _)| self.zebra_hosts.get(setting))
+            .map(|host| host.trim())
+            .filter(|host| !host.is_empty())
+    }
+
+    // everything that would stop a print, paths are checked after the drive map is applied
+    pub fn validate(&self) -> Vec<AppError> {
+        let mut problems = Vec::new();
+        let resolved = self.clone().with_resolved_paths();
+
+        for (name, path) in [("bom_path", &resolved.bom_path), ("snl_path", &resolved.snl_path), ("pdf_to_printer_path", &resolved.pdf_to_printer_path)] {
+            if !Path::new(path).is_file() {
+                problems.push(AppError::config("setting_path_missing", format!("{} does not point at a file", name)).with_path(path));
+            }
+        }
+        for (name, path) in [("config_path", &resolved.config_path), ("label_path", &resolved.label_path)] {
+            if !Path::new(path).is_dir() {
+                problems.push(AppError::config("setting_path_missing", format!("{} does not point at a folder", name)).with_path(path));
+            }
+        }
+        if !resolved.archive_path.trim().is_empty() && !Path::new(resolved.archive_path.trim()).is_dir() {
+            problems.push(AppError::config("setting_path_missing", "archive_path does not point at a folder").with_path(&resolved.archive_path));
+        }
+        for (name, printer) in self.printers() {
+            if get_printer_by_name(printer).is_none() {
+                problems.push(AppError::printer("printer_not_found", format!("{} '{}' is not installed", name, printer)).with_printer(printer));
+            }
+        }
+        if !self.fallback_printer.trim().is_empty() && get_printer_by_name(self.fallback_printer.trim()).is_none() {
+            problems.push(AppError::printer("printer_not_found", format!("fallback_printer '{}' is not installed", self.fallback_printer)).with_printer(&self.fallback_printer));
+        } else if self.fallback_printer.trim().is_empty() && get_default_printer().is_none() {
+            problems.push(AppError::printer("no_default_printer", "Windows has no default printer and no fallback printer is set"));
+        }
+        for mapping in &self.drive_map {
+            let letter = mapping.drive.trim().trim_end_matches(['\\', '/']).trim_end_matches(':');
+            if letter.len() != 1 || !letter.chars().all(|c| c.is_ascii_alphabetic()) || mapping.unc.trim().is_empty() {
+                problems.push(AppError::config("drive_map_invalid", format!("Drive mapping '{}' -> '{}' needs a drive letter and a server path", mapping.drive, mapping.unc)));
+            }
+        }
+        problems
+    }
+
+    fn drive_mapping_for(&self, path: &str) -> Option<&DriveMapping> {
+        let path = path.trim_start();
+        let mut chars = path.chars();
+        let letter = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+        if chars.next() != Some(':') || !matches!(chars.next(), None | Some('\\') | Some('/')) {
+            return None;
+        }
+        self.drive_map.iter().find(|m| {
+            m.drive.trim().trim_end_matches(['\\', '/']).trim_end_matches(':').eq_ignore_ascii_case(&letter.to_string())
+        })
+    }
 }
 
 #[tauri::command]
-pub fn save_settings(settings: Settings, app_handle: AppHandle) -> Result<(), String> {
-    let doc_path;
-    let file_path;
-    // if the DOC_PATH is build,  we navigate AppData, else go to the .env location
-    if env!("DOC_PATH") == "build" {
-        file_path = app_handle
-            .path()
-            .resolve("appSettings.json", BaseDirectory::AppData)
-            .map_err(|e| format!("Failed to resolve settings path: {}", e))?;
-    } else {
-        doc_path = env!("DOC_PATH");
-        file_path = PathBuf::from(doc_path).join("appSettings.json");
-    }
-    println!("dm{} fs{}", settings.dark_mode, settings.font_size);
-    let json_string = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize Settings to JSON: {}", e))?;
-
-    let mut file = File::create(&file_path)
-        .map_err(|e| format!("Failed to create file {}: {}", &file_path.display(), e))?;
-    file.write_all(json_string.as_bytes())
-        .map_err(|e| format!("Failed to write to file {}: {}", &file_path.display(), e))?;
+pub fn save_settings(settings: Settings, app_handle: AppHandle) -> Result<(), AppError> {
+    save_settings_in(&settings, &AppContext::from_app(&app_handle)?)?;
+    settings_changed(&settings, &app_handle)
+}
+
+pub fn save_settings_in(settings: &Settings, ctx: &AppContext) -> Result<(), AppError> {
+    write_json(&ctx.file(SETTINGS_FILE), settings)
+}
+
+fn settings_changed(settings: &Settings, app_handle: &AppHandle) -> Result<(), AppError> {
+    logging::set_level(settings.log_level);
 
     //update frontend
     app_handle
         .emit("settings-updated", ())
-        .map_err(|e| format!("Failed to emit settings-updated event: {}", e))?;
+        .map_err(|e| AppError::io("event_emit", format!("Failed to emit settings-updated event: {}", e)))?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn load_settings(app_handle: AppHandle) -> Result<Settings, String> {
+pub fn load_settings(app_handle: AppHandle) -> Result<Settings, AppError> {
     return internal_load_settings(&app_handle);
 }
 
-pub fn internal_load_settings(app_handle: &AppHandle) -> Result<Settings, String> {
-    let doc_path;
-    let file_path;
-    // if the DOC_PATH is build,  we navigate AppData, else go to the .env location
-    if env!("DOC_PATH") == "build" {
-        file_path = app_handle
-            .path()
-            .resolve("appSettings.json", BaseDirectory::AppData)
-            .map_err(|e| format!("Failed to resolve settings path: {}", e))?;
-
-
-    } else {
-        doc_path = env!("DOC_PATH");
-        file_path = PathBuf::from(doc_path).join("appSettings.json");
-    }
+pub fn internal_load_settings(app_handle: &AppHandle) -> Result<Settings, AppError> {
+    AppContext::from_app(app_handle)?.settings()
+}
 
+pub fn load_settings_in(ctx: &AppContext) -> Result<Settings, AppError> {
+    let file_path = ctx.file(SETTINGS_FILE);
 
-    if !fs::exists(&file_path).expect("Can't check existence of appSettings") {
-        create_app_settings(&file_path).map_err(|e| format!("Failed to create app ettings: {}", e))?;
+    if !fs::exists(&file_path).map_err(|e| AppError::io("settings_read", format!("Can't check existence of appSettings: {}", e)).with_path(&file_path))? {
+        create_app_settings(&file_path).map_err(|e| AppError::io("settings_write", format!("Failed to create app settings: {}", e)).with_path(&file_path))?;
     }
 
     let file = File::open(&file_path)
-        .map_err(|_| "Failed to open settings file")?;
+        .map_err(|e| AppError::io("settings_read", format!("Failed to open settings file: {}", e)).with_path(&file_path))?;
     let reader = BufReader::new(file);
 
     let json_value: Value = serde_json::from_reader(reader)
-        .map_err(|_| "Failed to process json file")?;
+        .map_err(|e| AppError::config("settings_parse", format!("Failed to process json file: {}", e)).with_path(&file_path))?;
     let settings: Settings = serde_json::from_value(json_value)
-        .map_err(|e| format!("Failed to parse JSON into Settings: {}", e))?;
+        .map_err(|e| AppError::config("settings_parse", format!("Failed to parse JSON into Settings: {}", e)).with_path(&file_path))?;
 
     Ok(settings)
 }
 
+pub fn doc_file_path(file_name: &str, app_handle: &AppHandle) -> Result<PathBuf, AppError> {
+    Ok(AppContext::from_app(app_handle)?.file(file_name))
+}
+
+pub fn write_json<T: Serialize>(path: &PathBuf, value: &T) -> Result<(), AppError> {
+    let json_string = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::io("json_write", format!("Failed to serialize to JSON: {}", e)).with_path(path))?;
+
+    let mut file = File::create(path)
+        .map_err(|e| AppError::io("json_write", format!("Failed to create file: {}", e)).with_path(path))?;
+    file.write_all(json_string.as_bytes())
+        .map_err(|e| AppError::io("json_write", format!("Failed to write to file: {}", e)).with_path(path))?;
+
+    Ok(())
+}
+
 fn create_app_settings(path: &PathBuf) -> Result<(), std::io::Error>{
     let settings = Settings {
         font_size: 16,
@@ -113,6 +280,14 @@ fn create_app_settings(path: &PathBuf) -> Result<(), std::io::Error>{
         label_printer_075_025: "\\\\PXSVSFS01\\075x025_Zebra".to_string(),
         label_printer_2_3: "\\\\PXSVSFS01\\2x3ZEBRA".to_string(),
         label_printer_4_6: "\\\\PXSVSFS01\\ZDesigner ZD621-203dpi ZPL".to_string(),
+        log_level: default_log_level(),
+        drive_map: default_drive_map(),
+        supervisors: Vec::new(),
+        fractional_quantity: FractionalQuantity::default(),
+        check_digit: CheckDigit::default(),
+        archive_path: String::new(),
+        fallback_printer: String::new(),
+        zebra_hosts: BTreeMap::new(),
     }; // anything to do with visual cut needs to be \\pxsvsfs01, otherwise there will be issues
     
     let json_string = serde_json::to_string_pretty(&settings)?;
@@ -126,3 +301,319 @@ fn create_app_settings(path: &PathBuf) -> Result<(), std::io::Error>{
 
     Ok(())
 }
+
+// portable copy of a station's setup, used to move settings between print stations
+#[derive(Serialize, Deserialize)]
+pub struct SettingsBundle {
+    version: u32,
+    exported_on: String,
+    settings: Settings,
+    #[serde(default)]
+    profiles: Vec<SettingsProfile>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SettingsProfile {
+    pub name: String,
+    pub settings: Settings,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct SettingsProfiles {
+    pub active: Option<String>,
+    pub profiles: Vec<SettingsProfile>,
+}
+
+#[tauri::command]
+pub fn export_settings(path: String, app_handle: AppHandle) -> Result<(), AppError> {
+    export_settings_in(&PathBuf::from(path), &AppContext::from_app(&app_handle)?)
+}
+
+pub fn export_settings_in(path: &PathBuf, ctx: &AppContext) -> Result<(), AppError> {
+    let bundle = SettingsBundle {
+        version: BUNDLE_VERSION,
+        exported_on: ctx.now().format("%Y-%m-%d %H:%M").to_string(),
+        settings: ctx.settings()?,
+        profiles: load_profiles(ctx)?.profiles,
+    };
+
+    write_json(path, &bundle)
+}
+
+#[tauri::command]
+pub fn import_settings(path: String, app_handle: AppHandle) -> Result<(), AppError> {
+    let settings = import_settings_in(&PathBuf::from(path), &AppContext::from_app(&app_handle)?)?;
+    settings_changed(&settings, &app_handle)
+}
+
+pub fn import_settings_in(path: &PathBuf, ctx: &AppContext) -> Result<Settings, AppError> {
+    let file = File::open(path)
+        .map_err(|e| AppError::io("settings_read", format!("Failed to open settings bundle: {}", e)).with_path(path))?;
+    let bundle: SettingsBundle = serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| AppError::config("settings_parse", format!("Failed to parse settings bundle: {}", e)).with_path(path))?;
+    if bundle.version > BUNDLE_VERSION {
+        return Err(AppError::config("settings_version", format!("Settings bundle version {} is newer than this app supports ({})", bundle.version, BUNDLE_VERSION)).with_path(path));
+    }
+
+    // imported profiles replace local ones with the same name
+    let mut profiles = load_profiles(ctx)?;
+    for profile in bundle.profiles {
+        profiles.profiles.retain(|p| p.name != profile.name);
+        profiles.profiles.push(profile);
+    }
+    profiles.active = None;
+    write_profiles(&profiles, ctx)?;
+
+    save_settings_in(&bundle.settings, ctx)?;
+    Ok(bundle.settings)
+}
+
+#[tauri::command]
+pub fn list_profiles(app_handle: AppHandle) -> Result<SettingsProfiles, AppError> {
+    load_profiles(&AppContext::from_app(&app_handle)?)
+}
+
+#[tauri::command]
+pub fn save_profile(name: String, settings: Settings, app_handle: AppHandle) -> Result<(), AppError> {
+    save_profile_in(name, settings, &AppContext::from_app(&app_handle)?)
+}
+
+pub fn save_profile_in(name: String, settings: Settings, ctx: &AppContext) -> Result<(), AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::config("profile_name", "Profile name can't be empty"));
+    }
+
+    let mut profiles = load_profiles(ctx)?;
+    profiles.profiles.retain(|p| p.name != name);
+    profiles.profiles.push(SettingsProfile { name: name.clone(), settings });
+    profiles.active = Some(name);
+    write_profiles(&profiles, ctx)
+}
+
+#[tauri::command]
+pub fn switch_profile(name: String, app_handle: AppHandle) -> Result<Settings, AppError> {
+    let settings = switch_profile_in(name, &AppContext::from_app(&app_handle)?)?;
+    settings_changed(&settings, &app_handle)?;
+    Ok(settings)
+}
+
+pub fn switch_profile_in(name: String, ctx: &AppContext) -> Result<Settings, AppError> {
+    let mut profiles = load_profiles(ctx)?;
+    let settings = profiles.profiles
+        .iter()
+        .find(|p| p.name == name)
+        .map(|p| p.settings.clone())
+        .ok_or_else(|| AppError::not_found("profile_not_found", format!("No settings profile named '{}'", name)))?;
+
+    profiles.active = Some(name);
+    write_profiles(&profiles, ctx)?;
+    save_settings_in(&settings, ctx)?;
+
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn delete_profile(name: String, app_handle: AppHandle) -> Result<(), AppError> {
+    delete_profile_in(name, &AppContext::from_app(&app_handle)?)
+}
+
+pub fn delete_profile_in(name: String, ctx: &AppContext) -> Result<(), AppError> {
+    let mut profiles = load_profiles(ctx)?;
+    profiles.profiles.retain(|p| p.name != name);
+    if profiles.active.as_deref() == Some(name.as_str()) {
+        profiles.active = None;
+    }
+    write_profiles(&profiles, ctx)
+}
+
+pub fn load_profiles(ctx: &AppContext) -> Result<SettingsProfiles, AppError> {
+    let file_path = ctx.file(PROFILES_FILE);
+    if !fs::exists(&file_path).map_err(|e| AppError::io("settings_read", format!("Can't check existence of settings profiles: {}", e)).with_path(&file_path))? {
+        return Ok(SettingsProfiles::default());
+    }
+
+    let file = File::open(&file_path)
+        .map_err(|e| AppError::io("settings_read", format!("Failed to open settings profiles: {}", e)).with_path(&file_path))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| AppError::config("settings_parse", format!("Failed to parse settings profiles: {}", e)).with_path(&file_path))
+}
+
+fn write_profiles(profiles: &SettingsProfiles, ctx: &AppContext) -> Result<(), AppError> {
+    write_json(&ctx.file(PROFILES_FILE), profiles)
+}
+
+#[tauri::command]
+pub fn resolve_path(path: String, app_handle: AppHandle) -> Result<ResolvedPath, AppError> {
+    let settings = internal_load_settings(&app_handle)?;
+    let resolved = settings.resolve_path(&path);
+
+    Ok(ResolvedPath {
+        drive: settings.drive_mapping_for(&path).map(|m| m.drive.clone()),
+        exists: PathBuf::from(&resolved).exists(),
+        input: path,
+        resolved,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::temp_context;
+    use chrono::{Local, TimeZone};
+
+    fn context(name: &str) -> AppContext {
+        temp_context(name, Local.with_ymd_and_hms(2025, 9, 3, 12, 0, 0).unwrap())
+    }
+
+    fn json(settings: &Settings) -> Value {
+        serde_json::to_value(settings).unwrap()
+    }
+
+    fn mapped(drives: &[(&str, &str)]) -> Settings {
+        let mut settings = context("settings-drive-map").settings().unwrap();
+        settings.drive_map = drives.iter().map(|(drive, unc)| DriveMapping { drive: drive.to_string(), unc: unc.to_string() }).collect();
+        settings
+    }
+
+    #[test]
+    fn mapped_drives_resolve_to_their_share() {
+        let settings = mapped(&[("P:", r"\\pxsvsfs01\Production\"), ("q", r"\\pxsvsfs01\Quality"), ("R:\\", r"\\pxsvsfs01\Rack/")]);
+
+        let cases = [
+            (r"P:\Final", r"\\pxsvsfs01\Production\Final"),
+            // drive letters in either case, however the mapping writes them
+            (r"p:\Final", r"\\pxsvsfs01\Production\Final"),
+            (r"Q:\Reports\A", r"\\pxsvsfs01\Quality\Reports\A"),
+            (r"r:\Labels", r"\\pxsvsfs01\Rack\Labels"),
+            ("  P:/Final", r"\\pxsvsfs01\Production/Final"),
+            // the share's trailing separator isn't doubled up
+            ("P:", r"\\pxsvsfs01\Production"),
+            (r"P:\", r"\\pxsvsfs01\Production\"),
+        ];
+        for (path, resolved) in cases {
+            assert_eq!(settings.resolve_path(path), resolved, "{}", path);
+        }
+    }
+
+    #[test]
+    fn other_paths_come_back_unchanged() {
+        let settings = mapped(&[("P:", r"\\pxsvsfs01\Production")]);
+
+        for path in [r"Z:\Final", r"PX:\Final", "P:Final", r"\\pxsvsfs01\Production\Final", "Final", "", r"C:\Program Files"] {
+            assert_eq!(settings.resolve_path(path), path, "{}", path);
+            assert!(settings.drive_mapping_for(path).is_none(), "{}", path);
+        }
+        assert_eq!(settings.drive_mapping_for(r"p:\Final").map(|m| m.drive.as_str()), Some("P:"));
+    }
+
+    #[test]
+    fn creates_defaults_on_first_load() {
+        let ctx = context("settings-defaults");
+
+        let settings = ctx.settings().unwrap();
+
+        assert!(ctx.file(SETTINGS_FILE).exists());
+        assert_eq!(settings.log_level, LevelFilter::Info);
+        assert_eq!(settings.drive_map.len(), 6);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let ctx = context("settings-round-trip");
+        let mut settings = ctx.settings().unwrap();
+        settings.font_size = 20;
+        settings.dark_mode = true;
+        settings.part_list = vec!["94A000004-A01".to_string()];
+        settings.log_level = LevelFilter::Debug;
+
+        save_settings_in(&settings, &ctx).unwrap();
+
+        assert_eq!(json(&ctx.settings().unwrap()), json(&settings));
+    }
+
+    #[test]
+    fn older_files_pick_up_new_fields() {
+        let ctx = context("settings-older-file");
+        let mut value = json(&ctx.settings().unwrap());
+        value.as_object_mut().unwrap().remove("log_level");
+        value.as_object_mut().unwrap().remove("drive_map");
+        value.as_object_mut().unwrap().remove("fractional_quantity");
+        value.as_object_mut().unwrap().remove("archive_path");
+        value.as_object_mut().unwrap().remove("fallback_printer");
+        value.as_object_mut().unwrap().remove("zebra_hosts");
+        fs::write(ctx.file(SETTINGS_FILE), value.to_string()).unwrap();
+
+        let settings = ctx.settings().unwrap();
+
+        assert_eq!(settings.log_level, LevelFilter::Info);
+        assert_eq!(settings.drive_map.len(), 6);
+        assert_eq!(settings.fractional_quantity, FractionalQuantity::Reject);
+        assert!(settings.archive_path.is_empty());
+        assert!(settings.zebra_hosts.is_empty());
+    }
+
+    #[test]
+    fn zebra_hosts_follow_the_printer_setting() {
+        let mut settings = context("settings-zebra-hosts").settings().unwrap();
+        settings.zebra_hosts.insert("label_printer_2_025".to_string(), " 10.1.2.3 ".to_string());
+        settings.zebra_hosts.insert("label_printer_2_3".to_string(), String::new());
+
+        assert_eq!(settings.zebra_host(r"\\pxsvsfs01\2x25zebra"), Some("10.1.2.3"));
+        assert_eq!(settings.zebra_host(&settings.label_printer_2_3.clone()), None);
+        assert_eq!(settings.zebra_host("Brother HL-2270DW series"), None);
+    }
+
+    #[test]
+    fn profiles_switch_settings() {
+        let ctx = context("settings-profiles");
+        let mut line_two = ctx.settings().unwrap();
+        line_two.clr_printer = "\\\\PXSVSFS01\\Line2".to_string();
+
+        save_profile_in(" Line 2 ".to_string(), line_two.clone(), &ctx).unwrap();
+        let switched = switch_profile_in("Line 2".to_string(), &ctx).unwrap();
+
+        assert_eq!(json(&switched), json(&line_two));
+        assert_eq!(json(&ctx.settings().unwrap()), json(&line_two));
+        assert_eq!(load_profiles(&ctx).unwrap().active.as_deref(), Some("Line 2"));
+
+        delete_profile_in("Line 2".to_string(), &ctx).unwrap();
+        let profiles = load_profiles(&ctx).unwrap();
+        assert!(profiles.profiles.is_empty());
+        assert_eq!(profiles.active, None);
+    }
+
+    #[test]
+    fn export_import_moves_settings_between_stations() {
+        let from = context("settings-export");
+        let to = context("settings-import");
+        let mut settings = from.settings().unwrap();
+        settings.label_path = "\\\\pxsvsfs01\\Production\\Labels".to_string();
+        save_settings_in(&settings, &from).unwrap();
+        save_profile_in("Line 1".to_string(), settings.clone(), &from).unwrap();
+        let bundle = from.file("bundle.json");
+
+        export_settings_in(&bundle, &from).unwrap();
+        let imported = import_settings_in(&bundle, &to).unwrap();
+
+        assert_eq!(json(&imported), json(&settings));
+        assert_eq!(json(&to.settings().unwrap()), json(&settings));
+        let profiles = load_profiles(&to).unwrap();
+        assert_eq!(profiles.profiles.len(), 1);
+        assert_eq!(profiles.active, None);
+    }
+
+    #[test]
+    fn rejects_newer_bundles() {
+        let ctx = context("settings-newer-bundle");
+        let bundle = ctx.file("bundle.json");
+        export_settings_in(&bundle, &ctx).unwrap();
+        let mut value: Value = serde_json::from_str(&fs::read_to_string(&bundle).unwrap()).unwrap();
+        value["version"] = Value::from(BUNDLE_VERSION + 1);
+        fs::write(&bundle, value.to_string()).unwrap();
+
+        let error = import_settings_in(&bundle, &ctx).err().unwrap();
+
+        assert_eq!(error.code, "settings_version");
+    }
+}
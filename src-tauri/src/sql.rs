@@ -1,104 +1,397 @@
-use tiberius::{Client, Config, AuthMethod};
+use tiberius::{Client, Config, AuthMethod, Query, Row};
 use tokio::net::TcpStream;
 use tokio_util::compat::{Compat, TokioAsyncReadCompatExt};
 use futures::TryStreamExt;
+use futures::future::BoxFuture;
 use std::env;
+use chrono::{NaiveDate, NaiveDateTime};
 use tauri::{AppHandle};
-use crate::settings;
-use crate::structs::{Order, PrintOrder};
+use log::warn;
+use crate::settings::Settings;
+use crate::context::AppContext;
+use crate::query_config::QueryConfig;
+use crate::notes;
+use crate::error::AppError;
+use crate::structs::{NoteRow, Order, OrderPage, OrderQuery, OrderSort, PrintItemRow, PrintOrder};
+
+const MAX_PAGE_SIZE: u32 = 500;
+
+// connection details for the ERP database, read from the .env file at build time
+#[derive(Clone, Default)]
+pub struct DbConfig {
+    pub host: String,
+    // None when DB_PORT isn't a number, reported once a query tries to connect
+    pub port: Option<u16>,
+    pub user: String,
+    pub password: String,
+    pub database: String,
+}
+
+impl DbConfig {
+    pub fn from_env() -> Self {
+        DbConfig {
+            host: env!("DB_HOST").to_string(),
+            port: env!("DB_PORT").parse().ok(),
+            user: env!("DB_USER").to_string(),
+            password: env!("DB_PASSWORD").to_string(),
+            database: env!("DB_NAME").to_string(),
+        }
+    }
+}
+
+// the ERP queries the app runs, SqlServer in the app and the cli, the in-memory ERP in tests.
+// which orders count is up to the QueryConfig rules passed in
+pub trait Erp: Send + Sync {
+    fn orders<'a>(&'a self, rules: &'a QueryConfig, query: OrderQuery) -> BoxFuture<'a, Result<OrderPage, AppError>>;
+    fn order_number_info<'a>(&'a self, rules: &'a QueryConfig, order_number: String) -> BoxFuture<'a, Result<Vec<Order>, AppError>>;
+    // the label and docs rows behind print_items, in print order with notes as stored
+    fn print_item_rows<'a>(&'a self, rules: &'a QueryConfig, order_number: String) -> BoxFuture<'a, Result<Vec<PrintItemRow>, AppError>>;
+    fn all_notes(&self) -> BoxFuture<'_, Result<Vec<NoteRow>, AppError>>;
+    fn common_parts(&self, order_number: String) -> BoxFuture<'_, Result<Vec<String>, AppError>>;
+}
+
+pub struct SqlServer {
+    db: DbConfig,
+}
+
+impl SqlServer {
+    pub fn new(db: DbConfig) -> Self {
+        SqlServer { db }
+    }
+}
+
+impl Erp for SqlServer {
+    fn orders<'a>(&'a self, rules: &'a QueryConfig, query: OrderQuery) -> BoxFuture<'a, Result<OrderPage, AppError>> {
+        Box::pin(orders(rules, query, &self.db))
+    }
+
+    fn order_number_info<'a>(&'a self, rules: &'a QueryConfig, order_number: String) -> BoxFuture<'a, Result<Vec<Order>, AppError>> {
+        Box::pin(order_number_info(rules, order_number, &self.db))
+    }
+
+    fn print_item_rows<'a>(&'a self, rules: &'a QueryConfig, order_number: String) -> BoxFuture<'a, Result<Vec<PrintItemRow>, AppError>> {
+        Box::pin(print_item_rows(rules, order_number, &self.db))
+    }
+
+    fn all_notes(&self) -> BoxFuture<'_, Result<Vec<NoteRow>, AppError>> {
+        Box::pin(all_notes(&self.db))
+    }
+
+    fn common_parts(&self, order_number: String) -> BoxFuture<'_, Result<Vec<String>, AppError>> {
+        Box::pin(common_parts(order_number, &self.db))
+    }
+}
 
 #[tauri::command]
-pub async fn get_orders() -> Result<Vec<Order>, String> {
-    let mut client = sql_setup().await?;
+pub async fn get_orders(query: Option<OrderQuery>, app_handle: AppHandle) -> Result<OrderPage, AppError> {
+    let ctx = AppContext::from_app(&app_handle)?;
+    ctx.erp().orders(&ctx.query_config()?, query.unwrap_or_default()).await
+}
 
-    let query =
-    "SELECT DISTINCT om.ORDNUM_10, om.PRTNUM_10, om.DUEQTY_10, 
+// the due window as [from, before), before being the day after due_to
+pub fn due_window(query: &OrderQuery) -> Result<(Option<NaiveDate>, Option<NaiveDate>), AppError> {
+    let parse = |field: &str, value: &Option<String>| match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(v) => NaiveDate::parse_from_str(v, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| AppError::config("order_query_invalid", format!("{} '{}' is not a date like 2025-09-03", field, v))),
+    };
+    let from = parse("due_from", &query.due_from)?;
+    let to = parse("due_to", &query.due_to)?;
+    Ok((from, to.and_then(|d| d.succ_opt())))
+}
+
+pub fn page_size(query: &OrderQuery) -> u32 {
+    query.page_size.clamp(1, MAX_PAGE_SIZE)
+}
+
+// LIKE pattern that matches the text literally
+fn like_escape(text: &str) -> String {
+    text.replace('[', "[[]").replace('%', "[%]").replace('_', "[_]")
+}
+
+// @P parameters for a query put together from rules and filters, bound in the order they were added.
+// only column names, sort direction and paging numbers go into the text itself
+#[derive(Clone, Default)]
+struct Params {
+    values: Vec<String>,
+}
+
+impl Params {
+    fn bind(&mut self, value: impl Into<String>) -> String {
+        self.values.push(value.into());
+        format!("@P{}", self.values.len())
+    }
+
+    // "(@P1, @P2)" for an IN, None for an empty list
+    fn list(&mut self, values: &[String]) -> Option<String> {
+        if values.is_empty() {
+            return None;
+        }
+        let params: Vec<String> = values.iter().map(|v| self.bind(v.trim())).collect();
+        Some(format!("({})", params.join(", ")))
+    }
+
+    // any of the columns starting with any of the prefixes, None for no prefixes
+    fn starts_with(&mut self, columns: &[&str], prefixes: &[String]) -> Option<String> {
+        if prefixes.is_empty() {
+            return None;
+        }
+        let mut alternatives = Vec::new();
+        for prefix in prefixes {
+            let p = self.bind(format!("{}%", like_escape(prefix.trim())));
+            alternatives.extend(columns.iter().map(|column| format!("{} LIKE {}", column, p)));
+        }
+        Some(format!("({})", alternatives.join(" OR ")))
+    }
+
+    fn query(self, sql: String) -> Query<'static> {
+        let mut query = Query::new(sql);
+        for value in self.values {
+            query.bind(value);
+        }
+        query
+    }
+}
+
+// "AND a AND b" for the conditions that apply, lined up under the WHERE
+fn and_all(conditions: impl IntoIterator<Item = Option<String>>) -> String {
+    conditions.into_iter().flatten().map(|c| format!("\n        AND {}", c)).collect()
+}
+
+// the page of orders and the count of all of them, over the same open orders, filters and parameters
+struct OrdersSql {
+    page: String,
+    count: String,
+    params: Params,
+}
+
+fn orders_sql(rules: &QueryConfig, query: &OrderQuery) -> Result<OrdersSql, AppError> {
+    let (due_from, due_before) = due_window(query)?;
+    let page_size = page_size(query);
+    let open = &rules.open_orders;
+    let status = open.status_filter(query.status.as_deref())?;
+    let mut params = Params::default();
+
+    let eligible = and_all([
+        params.starts_with(&["om.ORDNUM_10"], &open.order_prefixes),
+        params.list(&open.excluded_planners).map(|p| format!("om.PLANID_10 NOT IN {}", p)), // removes screws?
+        params.list(&open.statuses).map(|p| format!("om.STATUS_10 IN {}", p)),
+    ]);
+    let subassembly = params.starts_with(&["pm.PRTNUM_01"], &open.part_prefixes).unwrap_or_else(|| "1 = 1".to_string());
+    let parent = params.starts_with(&["ps.PARPRT_02"], &open.part_prefixes).unwrap_or_else(|| "1 = 1".to_string());
+
+    let search = query.search.trim();
+    let search = (!search.is_empty()).then(|| {
+        let p = params.bind(format!("%{}%", like_escape(search)));
+        format!("(ORDNUM_10 LIKE {p} OR ORDER_10 LIKE {p} OR PRTNUM_10 LIKE {p} OR ASSPRT LIKE {p})")
+    });
+    let filters = and_all([
+        search,
+        status.map(|status| format!("STATUS_10 = {}", params.bind(status))),
+        due_from.map(|from| format!("CURDUE_10 >= {}", params.bind(from.format("%Y%m%d").to_string()))),
+        due_before.map(|before| format!("CURDUE_10 < {}", params.bind(before.format("%Y%m%d").to_string()))),
+        params.starts_with(&["ORDNUM_10"], &query.series),
+        // the order's part or the subassembly it builds
+        params.starts_with(&["PRTNUM_10", "ASSPRT"], &query.families),
+    ]);
+
+    let sort = match query.sort {
+        OrderSort::OrderNumber => "ORDNUM_10",
+        OrderSort::PartNumber => "PRTNUM_10",
+        OrderSort::DueDate => "CURDUE_10",
+        OrderSort::DueQuantity => "DUEQTY_10",
+    };
+    let direction = if query.descending { "DESC" } else { "ASC" };
+    let offset = query.page as u64 * page_size as u64;
+
+    let open_orders = format!(
+    "WITH open_orders AS (
+SELECT DISTINCT om.ORDNUM_10, om.PRTNUM_10, om.DUEQTY_10, 
         CASE pm.TYPE_01 WHEN 'S'        THEN pm.PRTNUM_01
                                         ELSE om.PRTNUM_10
-        END AS ASSPRT, om.ORDER_10
+        END AS ASSPRT, om.ORDER_10, om.CURDUE_10, om.STATUS_10, om.RELDTE_10, om.CUSTID_10, om.PLANID_10, om.CMPQTY_10
 FROM    Requirement_Detail rd, Order_Master om, Product_Structure ps, Part_Master pm
 WHERE   om.ORDNUM_10 = rd.ORDNUM_11
-        AND (LEFT(om.ORDNUM_10, 1) = '5' OR LEFT(om.ORDNUM_10, 1) = '7')
         AND om.DUEQTY_10 > 0 
-        AND om.PLANID_10 != '000' 
         AND pm.PRTNUM_01 = ps.COMPRT_02
-        AND ps.PARPRT_02 = om.PRTNUM_10
-        AND om.STATUS_10 = '3'
-        AND ((pm.TYPE_01 = 'S' 
-                AND (LEFT(pm.PRTNUM_01, 2) = '02' OR LEFT(pm.PRTNUM_01, 3) = 'K02') )
-        OR (rd.PRTNUM_11 = ps.COMPRT_02  AND (LEFT(ps.PARPRT_02, 2) = '02' OR LEFT(ps.PARPRT_02, 3) = 'K02')))
+        AND ps.PARPRT_02 = om.PRTNUM_10{eligible}
+        AND ((pm.TYPE_01 = 'S' AND {subassembly})
+        OR (rd.PRTNUM_11 = ps.COMPRT_02 AND {parent}))
+)");
+
+    let page = format!(
+    "{open_orders}
+SELECT  ORDNUM_10, PRTNUM_10, DUEQTY_10, ASSPRT, ORDER_10,
+        CURDUE_10, RELDTE_10, CUSTID_10, PLANID_10, CMPQTY_10
+FROM    open_orders
+WHERE   1 = 1{filters}
+ORDER BY {sort} {direction}, ORDNUM_10 DESC
+OFFSET {offset} ROWS FETCH NEXT {page_size} ROWS ONLY");
+
+    // counted on its own so a page past the end still knows the total
+    let count = format!(
+    "{open_orders}
+SELECT  COUNT(*)
+FROM    open_orders
+WHERE   1 = 1{filters}");
+
+    Ok(OrdersSql { page, count, params })
+}
 
-ORDER BY om.ORDNUM_10 DESC"; // om.PLANID_10 != '000' removes screws?
+// the Order_Master columns both order queries read, as they come off the row
+struct OrderColumns<'a> {
+    order_number: Option<&'a str>,
+    order_number_full: Option<&'a str>,
+    part_number: Option<&'a str>,
+    due_quantity: Option<f64>,
+    assn_number: Option<&'a str>,
+    due_date: Option<NaiveDateTime>,
+    release_date: Option<NaiveDateTime>,
+    customer: Option<&'a str>,
+    planner: Option<&'a str>,
+    completed_quantity: Option<f64>,
+}
 
-    let mut stream = client
-        .query(query, &[])
+impl OrderColumns<'_> {
+    fn into_order(self) -> Result<Order, AppError> {
+        let due_quantity = required(self.due_quantity, "DUEQTY_10")?;
+        Ok(Order {
+            order_number: required(self.order_number, "ORDNUM_10")?.to_string(),
+            order_number_full: required(self.order_number_full, "ORDER_10")?.to_string(),
+            part_number: required(self.part_number, "PRTNUM_10")?.to_string(),
+            due_quantity,
+            assn_number: required(self.assn_number, "ASSPRT")?.to_string(),
+            due_date: self.due_date.map(|d| d.date()),
+            release_date: self.release_date.map(|d| d.date()),
+            customer: self.customer.map(|s| s.trim().to_string()).unwrap_or_default(),
+            planner: self.planner.map(|s| s.trim().to_string()).unwrap_or_default(),
+            // nothing completed yet when the ERP has no quantity, never below 0 once more than due was reported
+            remaining_quantity: (due_quantity - self.completed_quantity.unwrap_or_default()).max(0.0),
+        })
+    }
+}
+
+// a column the app can't do without, an error naming it when the ERP sent NULL
+fn required<T>(value: Option<T>, column: &str) -> Result<T, AppError> {
+    value.ok_or_else(|| AppError::database("db_column_null", format!("{} came back empty", column)))
+}
+
+// a date column that doesn't read as a date is the same on every row, so it stops the query
+fn date_column(row: &Row, index: usize, column: &str) -> Result<Option<NaiveDateTime>, AppError> {
+    row.try_get(index)
+        .map_err(|e| AppError::database("db_column_type", format!("{} is not a date: {}", column, e)))
+}
+
+// a broken order leaves the rest of the list usable
+fn push_order(orders: &mut Vec<Order>, order: Result<Order, AppError>) {
+    match order {
+        Ok(order) => orders.push(order),
+        Err(e) => warn!("skipping order row: {}", e.message),
+    }
+}
+
+async fn orders(rules: &QueryConfig, query: OrderQuery, db: &DbConfig) -> Result<OrderPage, AppError> {
+    let page_size = page_size(&query);
+    let sql = orders_sql(rules, &query)?;
+
+    let mut client = sql_setup(db).await?;
+    let total: Option<i32> = sql.params
+        .clone()
+        .query(sql.count)
+        .query(&mut client)
+        .await
+        .map_err(|e| AppError::database("db_query", format!("Query error: {}", e)))?
+        .into_row()
         .await
-        .map_err(|e| format!("Query error: {}", e))?;
+        .map_err(|e| AppError::database("db_row", format!("Row error: {}", e)))?
+        .and_then(|row| row.get(0));
+
+    let mut stream = sql.params
+        .query(sql.page)
+        .query(&mut client)
+        .await
+        .map_err(|e| AppError::database("db_query", format!("Query error: {}", e)))?;
 
     let mut orders = Vec::new();
 
-    while let Some(item) = stream.try_next().await.map_err(|e| format!("Row error: {}", e))? {
+    while let Some(item) = stream.try_next().await.map_err(|e| AppError::database("db_row", format!("Row error: {}", e)))? {
         if let Some(row) = item.into_row() {
-            let order_number: Option<&str> = row.get(0);
-            let part_number: Option<&str> = row.get(1);
-            let due_quantity: Option<f64> = row.get(2);
-            let assn_number: Option<&str> = row.get(3);
-            let order_number_full: Option<&str> = row.get(4);
-
-            orders.push(Order {
-                order_number: order_number.map(|s| s.to_string()).expect("ordernumber should have a value"),
-                order_number_full: order_number_full.map(|s| s.to_string()).expect("order should have a value"),
-                part_number: part_number.map(|s| s.to_string()).expect("part_number should have a value"),
-                due_quantity: due_quantity.expect("due_quantity should have a value"),
-                assn_number: assn_number.map(|s| s.to_string()).expect("assn_number should have a value"),
-            });
+            push_order(&mut orders, OrderColumns {
+                order_number: row.get(0),
+                part_number: row.get(1),
+                due_quantity: row.get(2),
+                assn_number: row.get(3),
+                order_number_full: row.get(4),
+                due_date: date_column(&row, 5, "CURDUE_10")?,
+                release_date: date_column(&row, 6, "RELDTE_10")?,
+                customer: row.get(7),
+                planner: row.get(8),
+                completed_quantity: row.get(9),
+            }.into_order());
         }
     }
 
-    Ok(orders)
+    Ok(OrderPage { orders, total: total.unwrap_or_default() as u32, page: query.page, page_size })
 }
 
 #[tauri::command]
-pub async fn get_order_number_info(order_number: String) -> Result<Vec<Order>, String> {
-    let mut client = sql_setup().await?;
+pub async fn get_order_number_info(order_number: String, app_handle: AppHandle) -> Result<Vec<Order>, AppError> {
+    let ctx = AppContext::from_app(&app_handle)?;
+    ctx.erp().order_number_info(&ctx.query_config()?, order_number).await
+}
 
-    let query =
+// an order by its number or its full number, with the lookup rules
+fn order_lookup_sql(rules: &QueryConfig, order_number: &str) -> (String, Params) {
+    let lookup = &rules.order_lookup;
+    let mut params = Params::default();
+    let order = params.bind(order_number);
+    let statuses = and_all([params.list(&lookup.statuses).map(|p| format!("om.STATUS_10 IN {}", p))]);
+    let subassembly = params.starts_with(&["pm.PRTNUM_01"], &lookup.subassembly_prefixes).unwrap_or_else(|| "1 = 1".to_string());
+
+    let query = format!(
     "SELECT DISTINCT om.ORDNUM_10, om.PRTNUM_10, om.DUEQTY_10, 
         CASE pm.TYPE_01 WHEN 'S'        THEN pm.PRTNUM_01
                                         ELSE om.PRTNUM_10
-        END AS ASSPRT
+        END AS ASSPRT, om.CURDUE_10, om.RELDTE_10, om.CUSTID_10, om.PLANID_10, om.CMPQTY_10
 FROM    Requirement_Detail rd, Order_Master om, Product_Structure ps, Part_Master pm
-WHERE   (om.ORDNUM_10 = @P1 OR om.ORDER_10 = @P1)
+WHERE   (om.ORDNUM_10 = {order} OR om.ORDER_10 = {order})
         AND om.ORDNUM_10 = rd.ORDNUM_11
         AND om.DUEQTY_10 > 0 
         AND pm.PRTNUM_01 = ps.COMPRT_02
-        AND ps.PARPRT_02 = om.PRTNUM_10
-        AND ((pm.TYPE_01 = 'S' AND (LEFT(pm.PRTNUM_01, 3) = '0' + '2' + 'A' OR LEFT(pm.PRTNUM_01, 4) = 'K' + '0' + '2' + 'A') )
-            OR rd.PRTNUM_11 = ps.COMPRT_02)";
-//      AND om.STATUS_10 = '3'
-        // so you can enter old shop orders to test
+        AND ps.PARPRT_02 = om.PRTNUM_10{statuses}
+        AND ((pm.TYPE_01 = 'S' AND {subassembly})
+            OR rd.PRTNUM_11 = ps.COMPRT_02)");
 
-    let mut stream = client
-        .query(query, &[&order_number])
+    (query, params)
+}
+
+async fn order_number_info(rules: &QueryConfig, order_number: String, db: &DbConfig) -> Result<Vec<Order>, AppError> {
+    let (query, params) = order_lookup_sql(rules, &order_number);
+
+    let mut client = sql_setup(db).await?;
+    let mut stream = params
+        .query(query)
+        .query(&mut client)
         .await
-        .map_err(|e| format!("Query error: {}", e))?;
+        .map_err(|e| AppError::database("db_query", format!("Query error: {}", e)).with_order(&order_number))?;
 
     let mut orders = Vec::new();
 
-    while let Some(item) = stream.try_next().await.map_err(|e| format!("Row error: {}", e))? {
+    while let Some(item) = stream.try_next().await.map_err(|e| AppError::database("db_row", format!("Row error: {}", e)))? {
         if let Some(row) = item.into_row() {
-            let order_number: Option<&str> = row.get(0);
-            let part_number: Option<&str> = row.get(1);
-            let due_quantity: Option<f64> = row.get(2);
-            let assn_number: Option<&str> = row.get(3);
-
-            orders.push(Order {
-                order_number: order_number.map(|s| s.to_string()).expect("ordernumber should have a value"),
-                order_number_full: order_number.map(|s| s.to_string()).expect("ordernumber should have a value"), // value not needed here
-                part_number: part_number.map(|s| s.to_string()).expect("part_number should have a value"),
-                due_quantity: due_quantity.expect("due_quantity should have a value"),
-                assn_number: assn_number.map(|s| s.to_string()).expect("assn_number should have a value"),
-            });
+            push_order(&mut orders, OrderColumns {
+                order_number: row.get(0),
+                order_number_full: row.get(0), // value not needed here
+                part_number: row.get(1),
+                due_quantity: row.get(2),
+                assn_number: row.get(3),
+                due_date: date_column(&row, 4, "CURDUE_10")?,
+                release_date: date_column(&row, 5, "RELDTE_10")?,
+                customer: row.get(6),
+                planner: row.get(7),
+                completed_quantity: row.get(8),
+            }.into_order());
         }
     }
 
@@ -106,38 +399,14 @@ WHERE   (om.ORDNUM_10 = @P1 OR om.ORDER_10 = @P1)
 }
 
 #[tauri::command]
-pub async fn get_print_items(order_number: String, app_handle: AppHandle) -> Result<Vec<PrintOrder>, String> {
-    let mut client = sql_setup().await?;
-
-    let query =
-    "SELECT  om.ORDNUM_10, om.PRTNUM_10, om.DUEQTY_10, 
-        CASE pm.TYPE_01 WHEN 'S'        THEN ps.COMPRT_02
-                                        ELSE om.PRTNUM_10
-        END AS ASSPRT,
-rd.PRTNUM_11, wn.NOTES_61
-FROM    Requirement_Detail rd, Windows_Notes wn, Order_Master om, Product_Structure ps, Part_Master pm
-WHERE   (om.ORDNUM_10 = @P1 OR om.ORDER_10 = @P1)
-        AND om.ORDNUM_10 = rd.ORDNUM_11
-        AND wn.COMPRT_61 = rd.PRTNUM_11
-        AND (LEFT(rd.PRTNUM_11, 3) = '94A'
-            OR LEFT(rd.PRTNUM_11, 4) = 'K94A'
-            OR rd.PRTNUM_11 = 'Initial DOCS'
-            OR rd.PRTNUM_11 = 'Final DOCS')
-        AND om.DUEQTY_10 > 0 
-        AND pm.PRTNUM_01 = ps.COMPRT_02
-        AND ps.PARPRT_02 = om.PRTNUM_10
-        AND ( (pm.TYPE_01 = 'S' AND wn.PRTNUM_61 = ps.COMPRT_02)
-        OR (rd.PRTNUM_11 = ps.COMPRT_02 AND wn.PRTNUM_61 = om.PRTNUM_10))
-
-ORDER BY wn.MAXID";
-//  AND om.STATUS_10 = '3'
-    // so you can enter old shop orders to test
-       
+pub async fn get_print_items(order_number: String, app_handle: AppHandle) -> Result<Vec<PrintOrder>, AppError> {
+    let ctx = AppContext::from_app(&app_handle)?;
+    print_items(order_number, &ctx.settings()?, &ctx.query_config()?, ctx.erp()).await
+}
 
-    let mut stream = client
-        .query(query, &[&order_number])
-        .await
-        .map_err(|e| format!("Query error: {}", e))?;
+// BOM, Config and SNL, then the order's label and docs notes with switched off, empty and omitted ones dropped
+pub async fn print_items(order_number: String, app_settings: &Settings, rules: &QueryConfig, erp: &dyn Erp) -> Result<Vec<PrintOrder>, AppError> {
+    let rows = erp.print_item_rows(rules, order_number.clone()).await?;
 
     let mut print_orders = Vec::new();
     
@@ -166,46 +435,133 @@ ORDER BY wn.MAXID";
         print_type: "SNL".to_string(),
         notes: "Serial Number List".to_string(),
     });
-    
-    let app_settings = settings::internal_load_settings(&app_handle)?;
 
-    while let Some(item) = stream.try_next().await.map_err(|e| format!("Row error: {}", e))? {
+    // remove if the label is in the omit list, then notes that are empty or start with ~
+    let rows: Vec<PrintItemRow> = rows.into_iter().filter(|row| !app_settings.part_list.iter().any(|omitted| omitted == row.print_type.trim())).collect();
+    let joined = notes::join_notes(rows.iter().map(|row| (row, row.notes.as_deref())), |row, more| {
+        warn!("dropping continuation note '{}' on {} with no note above it", more, row.print_type.trim())
+    });
+    for (row, notes) in joined {
+        print_orders.push(PrintOrder {
+            order_number: row.order_number.clone(),
+            part_number: row.part_number.clone(),
+            due_quantity: row.due_quantity,
+            assn_number: row.assn_number.clone(),
+            print_type: row.print_type.clone(),
+            notes,
+        });
+    }
+
+    Ok(print_orders)
+}
+
+// the label and docs notes of an order, in print order
+fn print_item_rows_sql(rules: &QueryConfig, order_number: &str) -> (String, Params) {
+    let lookup = &rules.print_items;
+    let mut params = Params::default();
+    let order = params.bind(order_number);
+    let statuses = and_all([params.list(&lookup.statuses).map(|p| format!("om.STATUS_10 IN {}", p))]);
+    let subassembly = params.starts_with(&["pm.PRTNUM_01"], &lookup.subassembly_prefixes).unwrap_or_else(|| "1 = 1".to_string());
+
+    // the label and docs parts stay fixed, notes::NoteKind reads them the same way
+    let query = format!(
+    "SELECT  om.ORDNUM_10, om.PRTNUM_10, om.DUEQTY_10, 
+        CASE pm.TYPE_01 WHEN 'S'        THEN ps.COMPRT_02
+                                        ELSE om.PRTNUM_10
+        END AS ASSPRT,
+rd.PRTNUM_11, wn.NOTES_61
+FROM    Requirement_Detail rd, Windows_Notes wn, Order_Master om, Product_Structure ps, Part_Master pm
+WHERE   (om.ORDNUM_10 = {order} OR om.ORDER_10 = {order})
+        AND om.ORDNUM_10 = rd.ORDNUM_11
+        AND wn.COMPRT_61 = rd.PRTNUM_11
+        AND (LEFT(rd.PRTNUM_11, 3) = '94A'
+            OR LEFT(rd.PRTNUM_11, 4) = 'K94A'
+            OR rd.PRTNUM_11 = 'Initial DOCS'
+            OR rd.PRTNUM_11 = 'Final DOCS')
+        AND om.DUEQTY_10 > 0 
+        AND pm.PRTNUM_01 = ps.COMPRT_02
+        AND ps.PARPRT_02 = om.PRTNUM_10{statuses}
+        AND ( (pm.TYPE_01 = 'S' AND {subassembly} AND wn.PRTNUM_61 = ps.COMPRT_02)
+        OR (rd.PRTNUM_11 = ps.COMPRT_02 AND wn.PRTNUM_61 = om.PRTNUM_10))
+
+ORDER BY wn.MAXID");
+
+    (query, params)
+}
+
+async fn print_item_rows(rules: &QueryConfig, order_number: String, db: &DbConfig) -> Result<Vec<PrintItemRow>, AppError> {
+    let (query, params) = print_item_rows_sql(rules, &order_number);
+
+    let mut client = sql_setup(db).await?;
+    let mut stream = params
+        .query(query)
+        .query(&mut client)
+        .await
+        .map_err(|e| AppError::database("db_query", format!("Query error: {}", e)).with_order(&order_number))?;
+
+    let mut rows = Vec::new();
+
+    while let Some(item) = stream.try_next().await.map_err(|e| AppError::database("db_row", format!("Row error: {}", e)))? {
         if let Some(row) = item.into_row() {
-            let order_number: Option<&str> = row.get(0);
-            let part_number: Option<&str> = row.get(1);
-            let due_quantity: Option<f64> = row.get(2);
-            let assn_number: Option<&str> = row.get(3);
-            let print_type: Option<&str> = row.get(4);
-            let notes: Option<&str> = row.get(5);
-            // remove if note empty OR if starting with ~ OR if the label is in the omit list
-            let c = notes.and_then(|s| s.chars().next());
-            let pt = print_type.map(|s| s.trim().to_string()).expect("print_type should have a value");
-            if notes == Some("") || c == Some('~') || app_settings.part_list.contains(&pt) {
-                continue;
-            } else if c == Some('?') { 
-                if let Some(last_order) = print_orders.last_mut() {
-                    last_order.notes.push_str(
-                        notes.expect("notes should have a value")
-                    );
-                }
-            } else {
-                print_orders.push(PrintOrder {
-                    order_number: order_number.map(|s| s.to_string()).expect("ordernumber should have a value"),
-                    part_number: part_number.map(|s| s.to_string()).expect("part_number should have a value"),
-                    due_quantity: due_quantity.expect("due_quantity should have a value"),
-                    assn_number: assn_number.map(|s| s.to_string()).expect("assn_number should have a value"),
-                    print_type: print_type.map(|s| s.to_string()).expect("print_type should have a value"),
-                    notes: notes.map(|s| s.to_string()).expect("notes should have a value"),
-                });
-            }
+            // a row printed with a column missing would go out wrong, so the order doesn't print
+            rows.push(print_item_row(&row).map_err(|e| e.with_order(&order_number))?);
         }
     }
 
-    Ok(print_orders)
+    Ok(rows)
+}
+
+fn print_item_row(row: &Row) -> Result<PrintItemRow, AppError> {
+    Ok(PrintItemRow {
+        order_number: required(row.get::<&str, _>(0), "ORDNUM_10")?.to_string(),
+        part_number: required(row.get::<&str, _>(1), "PRTNUM_10")?.to_string(),
+        due_quantity: required(row.get(2), "DUEQTY_10")?,
+        assn_number: required(row.get::<&str, _>(3), "ASSPRT")?.to_string(),
+        print_type: required(row.get::<&str, _>(4), "PRTNUM_11")?.to_string(),
+        notes: row.get::<&str, _>(5).map(|s| s.to_string()),
+    })
+}
+
+// every label and docs note in the ERP, grouped by part in the order they print
+async fn all_notes(db: &DbConfig) -> Result<Vec<NoteRow>, AppError> {
+    let mut client = sql_setup(db).await?;
+
+    let query =
+    "SELECT  wn.PRTNUM_61, wn.COMPRT_61, wn.NOTES_61
+FROM    Windows_Notes wn
+WHERE   (LEFT(wn.COMPRT_61, 3) = '94A'
+            OR LEFT(wn.COMPRT_61, 4) = 'K94A'
+            OR wn.COMPRT_61 = 'Initial DOCS'
+            OR wn.COMPRT_61 = 'Final DOCS')
+
+ORDER BY wn.PRTNUM_61, wn.MAXID";
+
+    let mut stream = client
+        .query(query, &[])
+        .await
+        .map_err(|e| AppError::database("db_query", format!("Query error: {}", e)))?;
+
+    let mut rows = Vec::new();
+
+    while let Some(item) = stream.try_next().await.map_err(|e| AppError::database("db_row", format!("Row error: {}", e)))? {
+        if let Some(row) = item.into_row() {
+            let part_number: Option<&str> = row.get(0);
+            let print_type: Option<&str> = row.get(1);
+            let notes: Option<&str> = row.get(2);
+
+            rows.push(NoteRow {
+                part_number: part_number.map(|s| s.trim().to_string()).unwrap_or_default(),
+                print_type: print_type.map(|s| s.trim().to_string()).unwrap_or_default(),
+                notes: notes.map(|s| s.to_string()),
+            });
+        }
+    }
+
+    Ok(rows)
 }
 
-pub async fn common_parts(order_number: String) -> Result<Vec<String>, String> {
-    let mut client = sql_setup().await?;
+async fn common_parts(order_number: String, db: &DbConfig) -> Result<Vec<String>, AppError> {
+    let mut client = sql_setup(db).await?;
 
     let query =
     "SELECT  om.ORDNUM_10, om.PRTNUM_10, om.DUEQTY_10, 
@@ -230,45 +586,432 @@ ORDER BY om.ORDNUM_10 DESC";
     let mut stream = client
         .query(query, &[&order_number])
         .await
-        .map_err(|e| format!("Query error: {}", e))?;
+        .map_err(|e| AppError::database("db_query", format!("Query error: {}", e)).with_order(&order_number))?;
 
     let mut common_parts = Vec::new();
 
-    while let Some(item) = stream.try_next().await.map_err(|e| format!("Row error: {}", e))? {
+    while let Some(item) = stream.try_next().await.map_err(|e| AppError::database("db_row", format!("Row error: {}", e)))? {
         if let Some(row) = item.into_row() {
             let common_part: Option<&str> = row.get(4);
-            common_parts.push(common_part.map(|s| s.to_string()).expect("common_part should have a value"));
+            match common_part {
+                Some(part) => common_parts.push(part.to_string()),
+                None => warn!("skipping empty common part on order {}", order_number),
+            }
         }
     }
 
     Ok(common_parts)
 }
 
-async fn sql_setup() -> Result<Client<Compat<TcpStream>>, String> {
+async fn sql_setup(db: &DbConfig) -> Result<Client<Compat<TcpStream>>, AppError> {
     //setup functcion for all SQL queries
-    // getting information from the .env file
-    let host = env!("DB_HOST");
-    let port: u16 = env!("DB_PORT").parse().unwrap();
-    let user = env!("DB_USER");
-    let password = env!("DB_PASSWORD");
-    let database = env!("DB_NAME");
+    let port = db.port
+        .ok_or_else(|| AppError::config("db_config", "DB_PORT in the .env file is not a port number"))?;
 
     let mut config = Config::new();
-    config.host(host);
+    config.host(&db.host);
     config.port(port);
-    config.authentication(AuthMethod::sql_server(user, password));
-    config.database(database);
+    config.authentication(AuthMethod::sql_server(&db.user, &db.password));
+    config.database(&db.database);
     config.trust_cert();
 
     // Connect
     let tcp = TcpStream::connect(config.get_addr())
         .await
-        .map_err(|e| format!("TCP connect error: {}", e))?;
+        .map_err(|e| AppError::database("db_connect", format!("TCP connect error: {}", e)))?;
     let tcp = tcp.compat();
 
     let client = Client::connect(config, tcp)
         .await
-        .map_err(|e| format!("DB connect error: {}", e))?;
+        .map_err(|e| AppError::database("db_connect", format!("DB connect error: {}", e)))?;
 
     Ok(client)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::temp_context;
+    use crate::erp_fixture::FixtureErp;
+    use crate::query_config::OpenOrderRules;
+    use chrono::Local;
+
+    // the settings a new install starts with
+    fn settings(name: &str) -> Settings {
+        temp_context(name, Local::now()).settings().unwrap()
+    }
+
+    fn rows(items: &[PrintOrder]) -> Vec<(&str, &str, &str)> {
+        items.iter().map(|i| (i.print_type.as_str(), i.assn_number.as_str(), i.notes.as_str())).collect()
+    }
+
+    #[tokio::test]
+    async fn print_items_keep_note_order_and_drop_skipped_notes() {
+        let items = print_items("5000001".to_string(), &settings("print-items-order"), &QueryConfig::default(), &FixtureErp::seeded()).await.unwrap();
+
+        assert_eq!(rows(&items), vec![
+            ("BOM", "", "Bill of Materials"),
+            ("Config", "", "Configuration Sheet (if found)"),
+            ("SNL", "", "Serial Number List"),
+            ("Initial DOCS", "02A000100", "P:\\Work Instructions?WI-100?clr"),
+            ("94A000003", "02A000100", "ProductLabel?Rev B?Lot 7"),
+            ("94A000004-A01", "02A000100", "BoxLabel"),
+            ("Final DOCS", "02A000100", "P:\\Final?FinalReport?100"),
+        ]);
+        assert!(items[3..].iter().all(|i| i.order_number == "5000001" && i.due_quantity == 10.0));
+    }
+
+    #[tokio::test]
+    async fn print_items_leave_out_omitted_labels() {
+        let mut app_settings = settings("print-items-omitted");
+        app_settings.part_list = vec!["94A000004-A01".to_string()];
+
+        let items = print_items("5000001".to_string(), &app_settings, &QueryConfig::default(), &FixtureErp::seeded()).await.unwrap();
+
+        assert!(items.iter().all(|i| i.print_type != "94A000004-A01"));
+        assert_eq!(items.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn print_items_come_from_the_subassembly() {
+        let items = print_items("SO-1002".to_string(), &settings("print-items-subassembly"), &QueryConfig::default(), &FixtureErp::seeded()).await.unwrap();
+
+        assert_eq!(rows(&items[3..]), vec![
+            ("K94A000005", "K02A000300", "RackLabel?K"),
+            ("Final DOCS", "K02A000300", "P:\\Final?RackReport"),
+        ]);
+        // BOM, Config and SNL keep the number as typed
+        assert_eq!(items[0].order_number, "SO-1002");
+        assert_eq!(items[3].order_number, "5000002");
+    }
+
+    #[tokio::test]
+    async fn print_items_for_an_unknown_order_are_the_fixed_rows() {
+        let items = print_items("5999999".to_string(), &settings("print-items-unknown"), &QueryConfig::default(), &FixtureErp::seeded()).await.unwrap();
+
+        assert_eq!(items.iter().map(|i| i.print_type.as_str()).collect::<Vec<_>>(), vec!["BOM", "Config", "SNL"]);
+    }
+
+    #[tokio::test]
+    async fn continuation_without_a_note_above_is_dropped() {
+        let mut erp = FixtureErp::seeded();
+        // only SNL left above the '?Lot 7' line
+        erp.windows_notes.retain(|wn| wn.maxid != 10 && wn.maxid != 20);
+
+        let items = print_items("5000001".to_string(), &settings("print-items-continuation"), &QueryConfig::default(), &erp).await.unwrap();
+
+        assert_eq!(items[2].notes, "Serial Number List");
+        assert_eq!(rows(&items[3..4]), vec![("94A000004-A01", "02A000100", "BoxLabel")]);
+        assert!(items.iter().all(|i| i.print_type != "94A000003"));
+    }
+
+    fn order_numbers(page: &OrderPage) -> Vec<&str> {
+        page.orders.iter().map(|o| o.order_number.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn orders_are_open_production_orders_newest_first() {
+        let page = FixtureErp::seeded().orders(&QueryConfig::default(), OrderQuery::default()).await.unwrap();
+
+        let summary: Vec<(&str, &str, &str)> = page.orders
+            .iter()
+            .map(|o| (o.order_number.as_str(), o.order_number_full.as_str(), o.assn_number.as_str()))
+            .collect();
+        assert_eq!(summary, vec![
+            ("7000003", "SO-1003", "02A000100"),
+            ("5000002", "SO-1002", "K02A000300"),
+            ("5000001", "SO-1001", "02A000100"),
+        ]);
+        assert_eq!(page.total, 3);
+    }
+
+    #[tokio::test]
+    async fn orders_filter_by_search_status_due_window_series_and_family() {
+        let erp = FixtureErp::seeded();
+        let rules = QueryConfig::default();
+        let query = |change: fn(&mut OrderQuery)| {
+            let mut query = OrderQuery::default();
+            change(&mut query);
+            query
+        };
+
+        let cases: Vec<(&str, OrderQuery, Vec<&str>)> = vec![
+            ("search order", query(|q| q.search = "so-1002".to_string()), vec!["5000002"]),
+            ("search part", query(|q| q.search = "02A0001".to_string()), vec!["7000003", "5000001"]),
+            ("search subassembly", query(|q| q.search = "K02A".to_string()), vec!["5000002"]),
+            ("released", query(|q| q.status = Some("3".to_string())), vec!["7000003", "5000002", "5000001"]),
+            ("any status the rules allow", query(|q| q.status = Some("any".to_string())), vec!["7000003", "5000002", "5000001"]),
+            ("due window", query(|q| {
+                q.due_from = Some("2025-09-01".to_string());
+                q.due_to = Some("2025-09-12".to_string());
+            }), vec!["5000002", "5000001"]),
+            ("series", query(|q| q.series = vec!["5".to_string()]), vec!["5000002", "5000001"]),
+            ("family", query(|q| q.families = vec!["K02".to_string()]), vec!["5000002"]),
+            ("sort by due date", query(|q| {
+                q.sort = OrderSort::DueDate;
+                q.descending = false;
+            }), vec!["7000003", "5000001", "5000002"]),
+        ];
+
+        for (case, query, expected) in cases {
+            let page = erp.orders(&rules, query).await.unwrap();
+            assert_eq!(order_numbers(&page), expected, "{}", case);
+            assert_eq!(page.total as usize, expected.len(), "{}", case);
+        }
+    }
+
+    #[tokio::test]
+    async fn orders_page_with_totals() {
+        let erp = FixtureErp::seeded();
+        let rules = QueryConfig::default();
+        let mut query = OrderQuery { page_size: 2, ..OrderQuery::default() };
+
+        let first = erp.orders(&rules, query.clone()).await.unwrap();
+        query.page = 1;
+        let second = erp.orders(&rules, query).await.unwrap();
+
+        assert_eq!(order_numbers(&first), vec!["7000003", "5000002"]);
+        assert_eq!(order_numbers(&second), vec!["5000001"]);
+        assert_eq!((first.total, second.total), (3, 3));
+    }
+
+    #[test]
+    fn orders_sql_binds_filters_in_order() {
+        let query = OrderQuery {
+            search: " 50%1 ".to_string(),
+            status: Some("3".to_string()),
+            due_from: Some("2025-09-01".to_string()),
+            due_to: Some("2025-09-12".to_string()),
+            series: vec!["5".to_string()],
+            families: vec!["K02".to_string()],
+            ..OrderQuery::default()
+        };
+
+        // no open order rules, so the filters are the only parameters
+        let rules = QueryConfig {
+            open_orders: OpenOrderRules { order_prefixes: vec![], excluded_planners: vec![], statuses: vec![], part_prefixes: vec![] },
+            ..QueryConfig::default()
+        };
+
+        let sql = orders_sql(&rules, &query).unwrap();
+
+        assert_eq!(sql.params.values, ["%50[%]1%", "3", "20250901", "20250913", "5%", "K02%"]);
+        let filters = "WHERE   1 = 1
+        AND (ORDNUM_10 LIKE @P1 OR ORDER_10 LIKE @P1 OR PRTNUM_10 LIKE @P1 OR ASSPRT LIKE @P1)
+        AND STATUS_10 = @P2
+        AND CURDUE_10 >= @P3
+        AND CURDUE_10 < @P4
+        AND (ORDNUM_10 LIKE @P5)
+        AND (PRTNUM_10 LIKE @P6 OR ASSPRT LIKE @P6)";
+        assert!(sql.page.contains(filters), "{}", sql.page);
+        assert!(sql.count.ends_with(filters), "{}", sql.count);
+    }
+
+    #[test]
+    fn orders_sql_sorts_and_pages() {
+        let query = OrderQuery { sort: OrderSort::DueQuantity, descending: false, page: 3, page_size: 25, ..OrderQuery::default() };
+
+        let sql = orders_sql(&QueryConfig::default(), &query).unwrap();
+
+        assert!(sql.page.ends_with("WHERE   1 = 1\nORDER BY DUEQTY_10 ASC, ORDNUM_10 DESC\nOFFSET 75 ROWS FETCH NEXT 25 ROWS ONLY"), "{}", sql.page);
+        // the count covers every page
+        assert!(sql.count.ends_with("SELECT  COUNT(*)\nFROM    open_orders\nWHERE   1 = 1"), "{}", sql.count);
+        assert!(!sql.count.contains("OFFSET"));
+    }
+
+    #[test]
+    fn orders_sql_takes_the_rules_as_parameters() {
+        let mut rules = QueryConfig::default();
+        rules.open_orders.order_prefixes = vec!["5".to_string(), "7".to_string()];
+        rules.open_orders.excluded_planners = vec!["000".to_string()];
+        rules.open_orders.statuses = vec!["3".to_string()];
+        rules.open_orders.part_prefixes = vec!["02".to_string()];
+
+        let sql = orders_sql(&rules, &OrderQuery { page_size: 500, ..OrderQuery::default() }).unwrap();
+
+        assert_eq!(sql.params.values, ["5%", "7%", "000", "3", "02%", "02%"]);
+        assert!(sql.page.contains("AND (om.ORDNUM_10 LIKE @P1 OR om.ORDNUM_10 LIKE @P2)\n        AND om.PLANID_10 NOT IN (@P3)\n        AND om.STATUS_10 IN (@P4)"), "{}", sql.page);
+        assert!(sql.page.contains("AND ((pm.TYPE_01 = 'S' AND (pm.PRTNUM_01 LIKE @P5))\n        OR (rd.PRTNUM_11 = ps.COMPRT_02 AND (ps.PARPRT_02 LIKE @P6)))"), "{}", sql.page);
+        assert!(sql.page.ends_with("OFFSET 0 ROWS FETCH NEXT 500 ROWS ONLY"));
+    }
+
+    #[test]
+    fn order_lookup_sql_matches_either_order_number() {
+        let (sql, params) = order_lookup_sql(&QueryConfig::default(), "SO-1006");
+
+        // no status rule by default, so old orders can be looked up
+        assert_eq!(params.values, ["SO-1006", "02A%", "K02A%"]);
+        assert!(sql.contains("WHERE   (om.ORDNUM_10 = @P1 OR om.ORDER_10 = @P1)"), "{}", sql);
+        assert!(sql.contains("AND ((pm.TYPE_01 = 'S' AND (pm.PRTNUM_01 LIKE @P2 OR pm.PRTNUM_01 LIKE @P3))"), "{}", sql);
+        assert!(!sql.contains("STATUS_10"));
+
+        let mut rules = QueryConfig::default();
+        rules.order_lookup.statuses = vec!["3".to_string(), " 4 ".to_string()];
+        let (sql, params) = order_lookup_sql(&rules, "5000001");
+        assert_eq!(params.values, ["5000001", "3", "4", "02A%", "K02A%"]);
+        assert!(sql.contains("AND ps.PARPRT_02 = om.PRTNUM_10\n        AND om.STATUS_10 IN (@P2, @P3)"), "{}", sql);
+    }
+
+    #[test]
+    fn print_item_rows_sql_takes_any_subassembly_by_default() {
+        let (sql, params) = print_item_rows_sql(&QueryConfig::default(), "5000001");
+
+        assert_eq!(params.values, ["5000001"]);
+        assert!(sql.contains("AND ( (pm.TYPE_01 = 'S' AND 1 = 1 AND wn.PRTNUM_61 = ps.COMPRT_02)"), "{}", sql);
+        assert!(sql.ends_with("ORDER BY wn.MAXID"));
+
+        let mut rules = QueryConfig::default();
+        rules.print_items.subassembly_prefixes = vec!["K02A".to_string()];
+        let (sql, params) = print_item_rows_sql(&rules, "5000002");
+        assert_eq!(params.values, ["5000002", "K02A%"]);
+        assert!(sql.contains("AND (pm.PRTNUM_01 LIKE @P2) AND wn.PRTNUM_61"), "{}", sql);
+    }
+
+    fn columns() -> OrderColumns<'static> {
+        OrderColumns {
+            order_number: Some("5000002"),
+            order_number_full: Some("SO-1002"),
+            part_number: Some("02B000200"),
+            due_quantity: Some(4.0),
+            assn_number: Some("K02A000300"),
+            due_date: NaiveDate::from_ymd_opt(2025, 9, 12).and_then(|d| d.and_hms_opt(0, 0, 0)),
+            release_date: None,
+            customer: Some("NORTHWIND   "),
+            planner: None,
+            completed_quantity: Some(1.0),
+        }
+    }
+
+    #[test]
+    fn order_columns_map_to_an_order() {
+        let order = columns().into_order().unwrap();
+
+        assert_eq!((order.order_number.as_str(), order.order_number_full.as_str(), order.assn_number.as_str()), ("5000002", "SO-1002", "K02A000300"));
+        assert_eq!(order.due_date, NaiveDate::from_ymd_opt(2025, 9, 12));
+        assert_eq!(order.release_date, None);
+        // CHAR columns come padded, missing ones are empty
+        assert_eq!((order.customer.as_str(), order.planner.as_str()), ("NORTHWIND", ""));
+    }
+
+    #[test]
+    fn remaining_quantity_is_due_less_completed() {
+        let order = columns().into_order().unwrap();
+        assert_eq!((order.due_quantity, order.remaining_quantity), (4.0, 3.0));
+        assert_eq!(OrderColumns { completed_quantity: None, ..columns() }.into_order().unwrap().remaining_quantity, 4.0);
+        assert_eq!(OrderColumns { completed_quantity: Some(6.0), ..columns() }.into_order().unwrap().remaining_quantity, 0.0);
+    }
+
+    #[test]
+    fn order_columns_the_app_needs_must_be_there() {
+        let missing_part = OrderColumns { part_number: None, ..columns() };
+        let e = missing_part.into_order().unwrap_err();
+        assert_eq!(e.code, "db_column_null");
+        assert!(e.message.contains("PRTNUM_10"), "{}", e.message);
+
+        assert_eq!(OrderColumns { due_quantity: None, ..columns() }.into_order().unwrap_err().code, "db_column_null");
+        // the list columns can be empty
+        assert!(OrderColumns { due_date: None, customer: None, planner: None, ..columns() }.into_order().is_ok());
+
+        let mut orders = Vec::new();
+        push_order(&mut orders, OrderColumns { order_number: None, ..columns() }.into_order());
+        push_order(&mut orders, columns().into_order());
+        assert_eq!(orders.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_page_past_the_end_still_has_the_total() {
+        let query = OrderQuery { page: 5, page_size: 2, ..OrderQuery::default() };
+
+        let page = FixtureErp::seeded().orders(&QueryConfig::default(), query).await.unwrap();
+
+        assert!(page.orders.is_empty());
+        assert_eq!(page.total, 3);
+    }
+
+    #[tokio::test]
+    async fn orders_reject_bad_dates() {
+        let query = OrderQuery { due_to: Some("12/09/2025".to_string()), ..OrderQuery::default() };
+
+        let e = FixtureErp::seeded().orders(&QueryConfig::default(), query).await.unwrap_err();
+        assert_eq!(e.code, "order_query_invalid");
+    }
+
+    #[tokio::test]
+    async fn orders_reject_a_status_the_rules_leave_out() {
+        let query = OrderQuery { status: Some("2".to_string()), ..OrderQuery::default() };
+
+        let e = FixtureErp::seeded().orders(&QueryConfig::default(), query).await.unwrap_err();
+        assert_eq!(e.code, "order_query_invalid");
+    }
+
+    #[tokio::test]
+    async fn order_lookup_ignores_status() {
+        let erp = FixtureErp::seeded();
+        let rules = QueryConfig::default();
+
+        let found = erp.order_number_info(&rules, "SO-1006".to_string()).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].order_number, "5000006");
+        assert!(erp.order_number_info(&rules, "5000007".to_string()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn open_order_rules_come_from_the_query_config() {
+        let erp = FixtureErp::seeded();
+        let rules = |change: fn(&mut QueryConfig)| {
+            let mut rules = QueryConfig::default();
+            change(&mut rules);
+            rules
+        };
+
+        let cases: Vec<(&str, QueryConfig, Vec<&str>)> = vec![
+            ("new order series", rules(|r| r.open_orders.order_prefixes.push("6".to_string())), vec!["7000003", "6000004", "5000002", "5000001"]),
+            ("any status", rules(|r| r.open_orders.statuses.clear()), vec!["7000003", "5000006", "5000002", "5000001"]),
+            ("planner 000 allowed", rules(|r| r.open_orders.excluded_planners.clear()), vec!["7000003", "5000005", "5000002", "5000001"]),
+            ("one product family", rules(|r| r.open_orders.part_prefixes = vec!["K02".to_string()]), vec!["5000002"]),
+        ];
+
+        for (case, rules, expected) in cases {
+            let page = erp.orders(&rules, OrderQuery::default()).await.unwrap();
+            assert_eq!(order_numbers(&page), expected, "{}", case);
+        }
+    }
+
+    #[tokio::test]
+    async fn lookup_rules_come_from_the_query_config() {
+        let erp = FixtureErp::seeded();
+        let mut rules = QueryConfig::default();
+        rules.order_lookup.statuses = vec!["3".to_string()];
+        rules.print_items.subassembly_prefixes = vec!["02A".to_string()];
+
+        assert!(erp.order_number_info(&rules, "SO-1006".to_string()).await.unwrap().is_empty());
+        let items = print_items("5000002".to_string(), &settings("print-items-rules"), &rules, &erp).await.unwrap();
+        assert_eq!(items.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn orders_carry_dates_customer_and_planner() {
+        let erp = FixtureErp::seeded();
+        let rules = QueryConfig::default();
+
+        let order = erp.order_number_info(&rules, "SO-1002".to_string()).await.unwrap().remove(0);
+        assert_eq!(order.due_date, NaiveDate::from_ymd_opt(2025, 9, 12));
+        assert_eq!(order.release_date, NaiveDate::from_ymd_opt(2025, 9, 1));
+        assert_eq!((order.customer.as_str(), order.planner.as_str()), ("NORTHWIND", "200"));
+        assert_eq!((order.due_quantity, order.remaining_quantity), (4.0, 3.0));
+
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["due_date"], "2025-09-12");
+        // the window sends back only what print needs
+        let sent: Order = serde_json::from_str(r#"{ "order_number": "5000002", "order_number_full": "5000002", "part_number": "02B000200", "due_quantity": 2, "assn_number": "K02A000300" }"#).unwrap();
+        assert_eq!(sent.due_date, None);
+    }
+
+    #[tokio::test]
+    async fn common_parts_come_from_kits_and_subassemblies() {
+        let erp = FixtureErp::seeded();
+
+        assert_eq!(erp.common_parts("5000001".to_string()).await.unwrap(), vec!["02S000500"]);
+        assert_eq!(erp.common_parts("5000002".to_string()).await.unwrap(), vec!["02S000600"]);
+    }
+}
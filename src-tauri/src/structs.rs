@@ -1,12 +1,111 @@
 use serde::{Serialize, Deserialize};
+use chrono::NaiveDate;
+use crate::settings::FractionalQuantity;
+use crate::error::AppError;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Order {
     pub order_number: String,
     pub order_number_full: String,
     pub part_number: String,
-    pub due_quantity: f64,
+    pub due_quantity: f64, // what to print, the window lets the operator change it
     pub assn_number: String,
+    // from Order_Master for the lists, print doesn't need them sent back
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>, // CURDUE_10
+    #[serde(default)]
+    pub release_date: Option<NaiveDate>, // RELDTE_10
+    #[serde(default)]
+    pub customer: String, // CUSTID_10
+    #[serde(default)]
+    pub planner: String, // PLANID_10
+    #[serde(default)]
+    pub remaining_quantity: f64, // DUEQTY_10 less CMPQTY_10, what's still to be built
+}
+
+impl Order {
+    // due_quantity as a count of units to label. whole numbers stored as 2.9999999 still count as 3
+    pub fn units(&self, fractional: FractionalQuantity) -> Result<u32, AppError> {
+        let quantity = self.due_quantity;
+        let invalid = |reason: &str| AppError::config("order_quantity_invalid", format!("Order {} has a due quantity of {}, {}", self.order_number, quantity, reason))
+            .with_order(&self.order_number);
+        if !quantity.is_finite() || quantity < 0.0 {
+            return Err(invalid("which can't be labeled"));
+        }
+
+        let units = if (quantity - quantity.round()).abs() < 1e-6 {
+            quantity.round()
+        } else {
+            match fractional {
+                FractionalQuantity::Reject => return Err(AppError::config("order_quantity_fractional", format!("Order {} has a due quantity of {}, labels need a whole number of units", self.order_number, quantity))
+                    .with_order(&self.order_number)),
+                FractionalQuantity::RoundUp => quantity.ceil(),
+                FractionalQuantity::RoundDown => quantity.floor(),
+            }
+        };
+        if units < 1.0 {
+            return Err(invalid("there is nothing to label"));
+        }
+        if units > f64::from(u32::MAX) {
+            return Err(invalid("more units than can be labeled"));
+        }
+        Ok(units as u32)
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderSort {
+    #[default]
+    OrderNumber,
+    PartNumber,
+    DueDate,
+    DueQuantity,
+}
+
+// what the welcome list asks get_orders for, on top of the open order rules in the query config.
+// dates are YYYY-MM-DD and the due window includes both ends, empty series / families mean any. status narrows the
+// list to one of the statuses the query config allows, None or "any" keeps all of them
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct OrderQuery {
+    pub search: String,
+    pub status: Option<String>,
+    pub due_from: Option<String>,
+    pub due_to: Option<String>,
+    pub series: Vec<String>,   // order number prefixes, 5 / 7
+    pub families: Vec<String>, // part number prefixes, 02 / K02
+    pub sort: OrderSort,
+    pub descending: bool,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl Default for OrderQuery {
+    // every open order, newest first
+    fn default() -> Self {
+        OrderQuery {
+            search: String::new(),
+            status: None,
+            due_from: None,
+            due_to: None,
+            series: Vec::new(),
+            families: Vec::new(),
+            sort: OrderSort::OrderNumber,
+            descending: true,
+            page: 0,
+            page_size: 25,
+        }
+    }
+}
+
+// one page of get_orders, total counts every order matching the filters
+#[derive(Serialize, Debug)]
+pub struct OrderPage {
+    pub orders: Vec<Order>,
+    pub total: u32,
+    pub page: u32,
+    pub page_size: u32,
 }
 
 #[derive(Serialize)]
@@ -19,10 +118,79 @@ pub struct PrintOrder {
     pub notes: String,
 }
 
+// one label or docs row behind get_print_items, notes as stored
+pub struct PrintItemRow {
+    pub order_number: String,
+    pub part_number: String,
+    pub due_quantity: f64,
+    pub assn_number: String,
+    pub print_type: String,
+    pub notes: Option<String>,
+}
+
+// one Windows_Notes line as stored, before continuation lines are joined
+pub struct NoteRow {
+    pub part_number: String,
+    pub print_type: String,
+    pub notes: Option<String>,
+}
 
 #[derive(Deserialize)]
 pub struct PrintOrderRow {
-    id: i32,
+    pub id: i32,
     pub print_type: String,
     pub notes: String,
-}
\ No newline at end of file
+}
+
+// which units of the order a print covers, for lots built in batches. left empty it prints the units not printed yet
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct PrintBatch {
+    pub quantity: Option<u32>,
+    pub start: Option<u32>,          // 0 based unit of the order, not a serial number
+    pub supervisor: Option<String>,  // initials approving a print past the due quantity
+}
+
+// result of a print that didn't fail, failures come back as an AppError
+#[derive(Serialize, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PrintOutcome {
+    // the PDF archive copies that didn't export, the print itself went through
+    Printed { copies: u32, archive_failures: Vec<AppError> },
+    SkippedNotFound { root: String, term: String },
+}
+
+impl PrintOutcome {
+    pub fn not_found(root: &str, term: &str) -> Self {
+        log::warn!("no document matching '{}' under {}", term, root);
+        PrintOutcome::SkippedNotFound { root: root.to_string(), term: term.to_string() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::testing::order;
+
+    #[test]
+    fn units_from_due_quantity() {
+        use FractionalQuantity::*;
+        let cases: &[(f64, FractionalQuantity, Result<u32, &str>)] = &[
+            (3.0, Reject, Ok(3)),
+            (2.9999999, Reject, Ok(3)),
+            (2.5, Reject, Err("order_quantity_fractional")),
+            (2.5, RoundUp, Ok(3)),
+            (2.5, RoundDown, Ok(2)),
+            (0.5, RoundDown, Err("order_quantity_invalid")),
+            (0.0, Reject, Err("order_quantity_invalid")),
+            (-2.0, RoundUp, Err("order_quantity_invalid")),
+            (f64::NAN, RoundUp, Err("order_quantity_invalid")),
+            (1e12, Reject, Err("order_quantity_invalid")),
+        ];
+
+        for (quantity, fractional, expected) in cases {
+            let units = order(*quantity).units(*fractional).map_err(|e| e.code);
+            assert_eq!(units, *expected, "{} with {:?}", quantity, fractional);
+        }
+    }
+}
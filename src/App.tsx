@@ -23,6 +23,19 @@ export type Settings = {
   label_printer_075_025: string;
   label_printer_2_3: string;
   label_printer_4_6: string;
+  log_level: string;
+  drive_map: DriveMapping[];
+  supervisors: string[];
+  fractional_quantity: "reject" | "round_up" | "round_down";
+  check_digit: "none" | "mod10" | "luhn" | "mod43";
+  archive_path: string;
+  fallback_printer: string;
+  zebra_hosts: Record<string, string>;
+};
+
+export type DriveMapping = {
+  drive: string;
+  unc: string;
 };
 
 export default function App() {
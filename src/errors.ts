@@ -0,0 +1,34 @@
+export type ErrorCategory = "not_found" | "printer" | "database" | "serial" | "config" | "report" | "io";
+
+export type AppError = {
+  category: ErrorCategory;
+  code: string;
+  message: string;
+  context: {
+    path: string | null;
+    printer: string | null;
+    order: string | null;
+  };
+};
+
+const remediation: { [key in ErrorCategory]: string } = {
+  not_found: "Check the file exists on the share and the notes point at the right folder.",
+  printer: "Check the printer name in Settings and that the printer is online.",
+  database: "Check the network connection to the ERP database.",
+  serial: "Check the serial number files in the app data folder.",
+  config: "Check the app settings.",
+  report: "Check the report and that Visual CUT is installed.",
+  io: "Check the app has access to the file.",
+};
+
+export const isAppError = (error: unknown): error is AppError =>
+  typeof error === "object" && error !== null && "category" in error && "message" in error;
+
+export const errorMessage = (error: unknown): string => {
+  if (!isAppError(error)) {
+    return String(error);
+  }
+  const details = [error.context.path, error.context.printer, error.context.order].filter((v) => v);
+  const detailText = details.length > 0 ? " (" + details.join(", ") + ")" : "";
+  return error.message + detailText + ". " + remediation[error.category];
+};
@@ -1,18 +1,24 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import "./../App.css";
-import {Box, Button, Card, TextField, Typography, CircularProgress, Checkbox, } from "@mui/material";
+import {Box, Button, Card, TextField, Typography, CircularProgress, Checkbox, Dialog, DialogTitle, DialogContent, DialogActions, List, ListItem, ListItemText, } from "@mui/material";
 import { DataGrid, GridColDef, GridRowSelectionModel  } from '@mui/x-data-grid';
 import Layout from './../Layout';
 import { useNavigate, useLocation } from "react-router-dom";
 import confetti from 'canvas-confetti';
 import MyAlert, { SnackbarMessage } from "../components/MyAlert";
+import { AppError, errorMessage } from "../errors";
 
 type Order = {
   order_number: string;
   part_number: string;
   due_quantity: number;
   assn_number: string;
+  due_date: string | null;
+  release_date: string | null;
+  customer: string;
+  planner: string;
+  remaining_quantity: number;
 };
 
 type PrintOrder = {
@@ -30,12 +36,40 @@ type PrintOrderRow = {
     notes: string;
 };
 
+type PrintOutcome =
+    | { status: "printed"; copies: number; archive_failures: AppError[] }
+    | { status: "skipped_not_found"; root: string; term: string };
+
+type LogEntry = {
+    timestamp: string;
+    level: string;
+    target: string;
+    message: string;
+};
+
+type PrinterHealth = {
+    role: string;
+    name: string;
+    state: "ready" | "printing" | "paused" | "offline" | "unknown" | "not_installed";
+    problems: string[];
+};
+
+type NoteLint = {
+    print_type: string;
+    notes: string;
+    error: AppError | null;
+};
+
 function MainPage() {
   const [order, setOrder] = useState<Order | null>(null);
   const [printOrderRows, setPrintOrderRows] = useState<PrintOrderRow[]>([]);
   const [dueQuantity, setDueQuantity] = useState("");
+  // until the quantity is changed, labels and Final DOCS print the units not printed yet
+  const [quantityEdited, setQuantityEdited] = useState(false);
   const [serialNumber, setSerialNumber] = useState("0"); //pull from document
   const [username, setUsername] = useState("");
+  const [supervisor, setSupervisor] = useState("");
+  const [printed, setPrinted] = useState<Record<number, number>>({});
   const [rowSelectionModel, setRowSelectionModel] = useState<GridRowSelectionModel>({ type: 'include', ids: new Set() });
   const [snackPack, setSnackPack] = useState<readonly SnackbarMessage[]>([]);
   const [open, setOpen] = useState(false);
@@ -45,6 +79,11 @@ function MainPage() {
   const [errorPrintAmount, setErrorPrintAmount] = useState("");
   const [errorSerialNumber, setErrorSerialNumber] = useState("");
   const [reprintRun, setReprintRun] = useState(Boolean);
+  const [logs, setLogs] = useState<LogEntry[] | null>(null);
+  const [noteLints, setNoteLints] = useState<NoteLint[] | null>(null);
+  const [previewUrl, setPreviewUrl] = useState<string | null>(null);
+  const [printerWarnings, setPrinterWarnings] = useState<PrinterHealth[] | null>(null);
+  const [printers, setPrinters] = useState<PrinterHealth[] | null>(null);
   const navigate = useNavigate();
   const location = useLocation();
 
@@ -75,6 +114,7 @@ function MainPage() {
                     // we assume only first bc only 'SHOULD' have 1 order
                     setOrder(trimOrderFields(data[0]));
                     setDueQuantity(data[0].due_quantity.toString());
+                    setQuantityEdited(false);
                     console.log(data)
                 })
                 .catch((error) => {
@@ -99,6 +139,21 @@ function MainPage() {
         }
     }, [location.state.orderNumber])
 
+    // units printed so far per label / Final DOCS row, by row id
+    const loadPrintProgress = () => {
+        invoke<Record<number, number>>('get_print_progress', { orderNumber: location.state.orderNumber, rows: printOrderRows })
+            .then((data) => {
+                setPrinted(data);
+            })
+            .catch((error) => {
+                console.error("Error getting print progress:", error);
+            });
+    }
+
+    useEffect(() => {
+        loadPrintProgress();
+    }, [location.state.orderNumber, printOrderRows])
+
     useEffect(() => {
         if (order?.order_number?.startsWith("7")) {
             setReprintRun(true);
@@ -148,12 +203,17 @@ function MainPage() {
         notes: order.notes.trim(),
     });
     
+    // the due quantity can be fractional (2.5 of a part sold by length), stepping goes to the next whole unit
     const handleQuantityDown = () => {
-        setDueQuantity((parseInt(dueQuantity)-1).toString());
+        setDueQuantity(Math.max(Math.ceil(parseFloat(dueQuantity) || 0) - 1, 0).toString());
+        setErrorPrintAmount("");
+        setQuantityEdited(true);
     }
 
     const handleQuantityUp = () => {
-        setDueQuantity((parseInt(dueQuantity)+1).toString());
+        setDueQuantity((Math.floor(parseFloat(dueQuantity) || 0) + 1).toString());
+        setErrorPrintAmount("");
+        setQuantityEdited(true);
     }
 
     const handleSerialNumberDown = () => {
@@ -171,16 +231,17 @@ function MainPage() {
     }
 
     const handlePrint = () => {
-        if (serialNumber.endsWith("69") || (((parseInt(serialNumber) % 100) < 69) && (parseInt(serialNumber) % 100) + parseInt(dueQuantity) > 69 )) {
+        if (serialNumber.endsWith("69") || (((parseInt(serialNumber) % 100) < 69) && (parseInt(serialNumber) % 100) + parseFloat(dueQuantity) > 69 )) {
             fireConfetti();
         }
         let printSuccessCount = selectedOrders.length;
+        let skippedCount = 0;
         for (const rowOrder of selectedOrders) {
-            invoke<string>('print', { 
+            invoke<PrintOutcome>('print', { 
                 order: {
                     order_number: location.state.orderNumber,
                     part_number: order?.part_number || "",
-                    due_quantity: parseInt(dueQuantity),
+                    due_quantity: order?.due_quantity || 0,
                     assn_number: order?.assn_number || ""
                 },
                 printOrderRow: {
@@ -191,16 +252,33 @@ function MainPage() {
                 user: username || "",
                 serialNumber: serialNumber,
                 reprintRun: reprintRun,
+                batch: {
+                    quantity: quantityEdited ? Number(dueQuantity) : null,
+                    start: null,
+                    supervisor: supervisor || null,
+                },
             })
                 .then((data) => {
                     console.log("rust output", data);
-                    const message = "success for " + rowOrder.print_type + " " + rowOrder.notes;
-                    console.log(message);
+                    if (data.status == "skipped_not_found") {
+                        skippedCount++;
+                        const message = "Skipped " + rowOrder.print_type + ": nothing matching " + data.term + " in " + data.root;
+                        const type = "warning";
+                        setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
+                    } else if (data.archive_failures.length > 0) {
+                        // printed, but quality's PDF copy is missing
+                        const message = "Printed " + rowOrder.print_type + " but not archived: " + data.archive_failures.map(errorMessage).join(", ");
+                        const type = "warning";
+                        setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
+                    } else {
+                        console.log("success for " + rowOrder.print_type + " " + rowOrder.notes);
+                    }
                     printSuccessCount--;
                     if (printSuccessCount == 0) {
-                        const message = "Successful print";
-                        const type = "success";
+                        const message = skippedCount > 0 ? "Print finished, " + skippedCount + " skipped" : "Successful print";
+                        const type = skippedCount > 0 ? "warning" : "success";
                         setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
+                        loadPrintProgress();
                         invoke<string>('get_serial_number', { } )
                             .then((data) => {
                                 setSerialNumber(data);
@@ -213,7 +291,7 @@ function MainPage() {
                 })
                 .catch((error) => {
                     console.error("Error printing:", error);
-                    const message = "Error printing: " + rowOrder?.print_type + " Error: " + error;
+                    const message = "Error printing: " + rowOrder?.print_type + " Error: " + errorMessage(error);
                     const type = "warning";
                     setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
                 });
@@ -222,10 +300,107 @@ function MainPage() {
         // navigate('/done');
     }
 
+    // warns about paused, offline or empty printers before anything is sent, the health check itself never blocks a print
+    const handlePrintClick = () => {
+        invoke<PrinterHealth[]>('check_print_health', { printOrderRows: selectedOrders })
+            .then((data) => {
+                if (data.length > 0) {
+                    setPrinterWarnings(data);
+                } else {
+                    handlePrint();
+                }
+            })
+            .catch((error) => {
+                console.error("Error checking printers:", error);
+                handlePrint();
+            });
+    }
+
+    const handleShowPrinters = () => {
+        invoke<PrinterHealth[]>('printer_dashboard', { })
+            .then((data) => {
+                setPrinters(data);
+            })
+            .catch((error) => {
+                console.error("Error getting printers:", error);
+                const message = "Error getting printers: " + errorMessage(error);
+                const type = "warning";
+                setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
+            });
+    }
+
     const handleCancel = () => {
         navigate('/');
     }
 
+    // one label / Final DOCS row, rendered for the serial in the window without printing
+    const previewRow = selectedOrders.length == 1 && selectedOrders[0].id > 3 && selectedOrders[0].print_type.toLocaleLowerCase() != "initial docs" ? selectedOrders[0] : null;
+
+    const handlePreview = () => {
+        if (!previewRow) {
+            return;
+        }
+        invoke<ArrayBuffer>('preview_label', {
+            order: {
+                order_number: location.state.orderNumber,
+                part_number: order?.part_number || "",
+                due_quantity: order?.due_quantity || 0,
+                assn_number: order?.assn_number || ""
+            },
+            printOrderRow: previewRow,
+            serialNumber: serialNumber,
+        })
+            .then((data) => {
+                setPreviewUrl(URL.createObjectURL(new Blob([data], { type: "application/pdf" })));
+            })
+            .catch((error) => {
+                console.error("Error previewing:", error);
+                const message = "Error previewing: " + previewRow.print_type + " Error: " + errorMessage(error);
+                const type = "warning";
+                setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
+            });
+    }
+
+    const handleClosePreview = () => {
+        if (previewUrl) {
+            URL.revokeObjectURL(previewUrl);
+        }
+        setPreviewUrl(null);
+    }
+
+    const handleShowLogs = () => {
+        invoke<LogEntry[]>('get_recent_logs', { limit: 25, minLevel: "WARN" })
+            .then((data) => {
+                setLogs(data.reverse());
+            })
+            .catch((error) => {
+                console.error("Error getting logs:", error);
+            });
+    }
+
+    const handleOpenArchive = () => {
+        invoke('open_order_archive', { orderNumber: location.state.orderNumber })
+            .catch((error) => {
+                console.error("Error opening archive:", error);
+                const message = "Error opening archive: " + errorMessage(error);
+                const type = "warning";
+                setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
+            });
+    }
+
+    const handleCheckNotes = () => {
+        invoke<NoteLint[]>('lint_order_notes', { orderNumber: location.state.orderNumber })
+            .then((data) => {
+                setNoteLints(data);
+            })
+            .catch((error) => {
+                console.error("Error checking notes:", error);
+                const message = "Error checking notes: " + errorMessage(error);
+                const type = "warning";
+                setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
+            });
+    }
+
     const handleStarting = () => {
         const docsString = "initial docs";
         // if one of them aren't selected select all
@@ -302,10 +477,15 @@ function MainPage() {
 
     const columns: GridColDef[] = [
         { field: 'print_type', headerName: 'Print Type', width: 170 },
-        { field: 'notes', headerName: 'Notes', width: 530, 
+        { field: 'notes', headerName: 'Notes', width: 450, 
             sortable: false,
             description: 'The note section of the BOM', 
         },
+        { field: 'printed', headerName: 'Printed', width: 80,
+            sortable: false,
+            description: 'Units of this order already printed',
+            valueGetter: (_value, row: PrintOrderRow) => printed[row.id],
+        },
     ];
 
     const onChangePrintAmount = (event: React.ChangeEvent<HTMLInputElement>) => {
@@ -318,6 +498,7 @@ function MainPage() {
             setErrorPrintAmount("Requires a number");
         }
         setDueQuantity(newValue);
+        setQuantityEdited(true);
     };
     
     const onChangeSerialNumber = (event: React.ChangeEvent<HTMLInputElement>) => {
@@ -346,8 +527,12 @@ function MainPage() {
                         <Box sx={{ display: 'flex', flexDirection: 'column',  alignItems: 'center', justifyContent: 'space-around', height: '100%', minWidth: 300}}>
                             <Typography>Order Number: {order?.order_number}</Typography>
                             <Typography>Due Quantity: {order?.due_quantity}</Typography>
+                            <Typography>Remaining: {order?.remaining_quantity}</Typography>
                             <Typography>Part Number: {order?.part_number}</Typography>
                             <Typography>Assn Number: {order?.assn_number}</Typography>
+                            <Typography>Due Date: {order?.due_date ?? "none"}</Typography>
+                            <Typography>Customer: {order?.customer}</Typography>
+                            <Typography>Planner: {order?.planner}</Typography>
                         </Box>
                             <Box sx={{ display: 'flex', flexDirection: 'row',  alignItems: 'center', justifyContent: 'space-between', height: '100%', gap: '0.25em', p: '0.25em'}}>
                                 <Button  
@@ -364,7 +549,7 @@ function MainPage() {
                                     value={dueQuantity}
                                     onChange={onChangePrintAmount}
                                     sx={{width: 150}}
-                                    helperText={errorPrintAmount}
+                                    helperText={errorPrintAmount || (quantityEdited ? "" : "Units not printed yet")}
                                     error={!!errorPrintAmount}
                                     
                                 />
@@ -489,12 +674,33 @@ function MainPage() {
                         }}
                         required
                     />
+                    <TextField  
+                        id="supervisor-textfield" 
+                        label="Supervisor" 
+                        autoComplete="off"
+                        variant="outlined" 
+                        value={supervisor}
+                        onChange={(event: React.ChangeEvent<HTMLInputElement>) => {
+                            setSupervisor(event.target.value);
+                        }}
+                        helperText="Only to print past the due quantity"
+                        sx={{width: 150}}
+                    />
+
+                    <Button 
+                        id="preview-button" 
+                        variant="outlined"
+                        onClick={handlePreview}
+                        disabled={!previewRow}
+                    >
+                        Preview
+                    </Button>
 
                     <Button 
                         id="print-button" 
                         variant="outlined"
-                        onClick={handlePrint}
-                        disabled={!usernameFilled}
+                        onClick={handlePrintClick}
+                        disabled={!usernameFilled || !!errorPrintAmount}
                     >
                         Print
                     </Button>
@@ -506,6 +712,38 @@ function MainPage() {
                     >
                         Cancel
                     </Button>
+
+                    <Button
+                        id="logs-button"
+                        variant="outlined"
+                        onClick={handleShowLogs}
+                    >
+                        Logs
+                    </Button>
+
+                    <Button
+                        id="check-notes-button"
+                        variant="outlined"
+                        onClick={handleCheckNotes}
+                    >
+                        Check Notes
+                    </Button>
+
+                    <Button
+                        id="open-archive-button"
+                        variant="outlined"
+                        onClick={handleOpenArchive}
+                    >
+                        Open Archive
+                    </Button>
+
+                    <Button
+                        id="printers-button"
+                        variant="outlined"
+                        onClick={handleShowPrinters}
+                    >
+                        Printers
+                    </Button>
                     
                 </Box>
       
@@ -516,6 +754,89 @@ function MainPage() {
                 messageInfo={messageInfo}
                 setMessageInfo={setMessageInfo}
             />
+            <Dialog open={logs !== null} onClose={() => setLogs(null)} maxWidth="md" fullWidth>
+                <DialogTitle>Recent Warnings and Errors</DialogTitle>
+                <DialogContent>
+                    <List dense>
+                        {logs?.map((entry, index) => (
+                            <ListItem key={index}>
+                                <ListItemText primary={entry.message} secondary={entry.timestamp + " " + entry.level + " " + entry.target} />
+                            </ListItem>
+                        ))}
+                        {logs?.length === 0 && (
+                            <Typography variant="body2">No recent warnings or errors.</Typography>
+                        )}
+                    </List>
+                </DialogContent>
+                <DialogActions>
+                    <Button onClick={() => setLogs(null)}>Close</Button>
+                </DialogActions>
+            </Dialog>
+            <Dialog open={noteLints !== null} onClose={() => setNoteLints(null)} maxWidth="md" fullWidth>
+                <DialogTitle>Note Problems</DialogTitle>
+                <DialogContent>
+                    <List dense>
+                        {noteLints?.filter((lint) => lint.error).map((lint, index) => (
+                            <ListItem key={index}>
+                                <ListItemText primary={lint.error?.message} secondary={lint.print_type} />
+                            </ListItem>
+                        ))}
+                        {noteLints?.every((lint) => !lint.error) && (
+                            <Typography variant="body2">All {noteLints.length} notes are valid.</Typography>
+                        )}
+                    </List>
+                </DialogContent>
+                <DialogActions>
+                    <Button onClick={() => setNoteLints(null)}>Close</Button>
+                </DialogActions>
+            </Dialog>
+            <Dialog open={previewUrl !== null} onClose={handleClosePreview} maxWidth="md" fullWidth>
+                <DialogTitle>Preview: {previewRow?.print_type} {serialNumber}</DialogTitle>
+                <DialogContent>
+                    {previewUrl && (
+                        <iframe src={previewUrl} title="Label preview" style={{ width: '100%', height: '30em', border: 0 }} />
+                    )}
+                </DialogContent>
+                <DialogActions>
+                    <Button onClick={handleClosePreview}>Close</Button>
+                    <Button onClick={() => { handleClosePreview(); handlePrintClick(); }} disabled={!usernameFilled || !!errorPrintAmount}>Print</Button>
+                </DialogActions>
+            </Dialog>
+            <Dialog open={printerWarnings !== null} onClose={() => setPrinterWarnings(null)} maxWidth="sm" fullWidth>
+                <DialogTitle>Printer Problems</DialogTitle>
+                <DialogContent>
+                    <List dense>
+                        {printerWarnings?.map((printer, index) => (
+                            <ListItem key={index}>
+                                <ListItemText primary={printer.name + ": " + printer.problems.join(", ")} secondary={printer.role} />
+                            </ListItem>
+                        ))}
+                    </List>
+                </DialogContent>
+                <DialogActions>
+                    <Button onClick={() => setPrinterWarnings(null)}>Cancel</Button>
+                    <Button onClick={() => { setPrinterWarnings(null); handlePrint(); }}>Print Anyway</Button>
+                </DialogActions>
+            </Dialog>
+            <Dialog open={printers !== null} onClose={() => setPrinters(null)} maxWidth="md" fullWidth>
+                <DialogTitle>Printers</DialogTitle>
+                <DialogContent>
+                    <List dense>
+                        {printers?.map((printer, index) => (
+                            <ListItem key={index}>
+                                <ListItemText
+                                    primary={printer.name + " (" + printer.state.replace("_", " ") + ")"}
+                                    secondary={printer.role + (printer.problems.length > 0 ? ": " + printer.problems.join(", ") : "")}
+                                    slotProps={{ primary: { color: printer.problems.length > 0 ? "error" : undefined } }}
+                                />
+                            </ListItem>
+                        ))}
+                    </List>
+                </DialogContent>
+                <DialogActions>
+                    <Button onClick={() => setPrinters(null)}>Close</Button>
+                </DialogActions>
+            </Dialog>
         </>
         ) : (
             <Box sx={{ display: 'flex', flexDirection: 'row',  alignItems: 'center', justifyContent: 'center', gap: '1em', height: '100%', minHeight: '38em'}}>
@@ -3,14 +3,27 @@ import lightLogo from "./../assets/PixusLogoHD.png";
 import darkLogo from "./../assets/PixusLogoHDDarkmode.png";
 import { invoke } from "@tauri-apps/api/core";
 import "./../App.css";
-import { Box, Button,  CircularProgress,  Paper, TextField, Typography, List, ListItem, ListItemText, Switch, IconButton, ListItemSecondaryAction, Divider, useTheme } from "@mui/material";
+import { Box, Button,  CircularProgress,  Paper, TextField, Typography, List, ListItem, ListItemText, Switch, IconButton, ListItemSecondaryAction, Divider, MenuItem, useTheme } from "@mui/material";
 import Layout from './../Layout';
 import { useNavigate } from "react-router-dom";
 import KeyboardBackspaceIcon from '@mui/icons-material/KeyboardBackspace';
 import DeleteIcon from '@mui/icons-material/Delete';
 import AddIcon from '@mui/icons-material/Add';
 import MyAlert, { SnackbarMessage } from "../components/MyAlert";
-import { Settings } from './../App';
+import { errorMessage } from "../errors";
+import { DriveMapping, Settings } from './../App';
+
+type ResolvedPath = {
+  input: string;
+  resolved: string;
+  drive: string | null;
+  exists: boolean;
+};
+
+type SettingsProfiles = {
+  active: string | null;
+  profiles: { name: string; settings: Settings }[];
+};
 
 
 // font size, avoid list, dark mode
@@ -32,10 +45,23 @@ function SettingsPage() {
     label_printer_075_025: '',
     label_printer_2_3: '',
     label_printer_4_6: '',
+    log_level: 'INFO',
+    drive_map: [],
+    supervisors: [],
+    fractional_quantity: 'reject',
+    check_digit: 'none',
+    archive_path: '',
+    fallback_printer: '',
+    zebra_hosts: {},
   });
   const [errors, setErrors] = useState<{ [key in keyof Settings]?: string }>({});
 
   const [inputValue, setInputValue] = useState('');
+  const [profiles, setProfiles] = useState<SettingsProfiles | null>(null);
+  const [profileName, setProfileName] = useState('');
+  const [bundlePath, setBundlePath] = useState('');
+  const [testPath, setTestPath] = useState('');
+  const [resolvedPath, setResolvedPath] = useState<ResolvedPath | null>(null);
   const [snackPack, setSnackPack] = useState<readonly SnackbarMessage[]>([]);
   const [open, setOpen] = useState(false);
   const [messageInfo, setMessageInfo] = useState<SnackbarMessage | undefined>(
@@ -57,8 +83,101 @@ function SettingsPage() {
     setCurrentSettings( prev => prev ? {...prev, part_list: prev.part_list.filter((_, i) => i !== index) } : prev)
   };
 
+  const handleDriveChange = (index: number, key: keyof DriveMapping) => (event: React.ChangeEvent<HTMLInputElement>) => {
+    const value = event.target.value;
+    setCurrentSettings(prev => prev ? {...prev, drive_map: prev.drive_map.map((m, i) => i === index ? { ...m, [key]: value } : m) } : prev)
+  };
+
+  const handleDriveAdd = () => {
+    setCurrentSettings(prev => prev ? {...prev, drive_map: [...prev.drive_map, { drive: '', unc: '' }] } : prev)
+  };
+
+  const handleDriveRemove = (index: number) => {
+    setCurrentSettings(prev => prev ? {...prev, drive_map: prev.drive_map.filter((_, i) => i !== index) } : prev)
+  };
+
+  const handleResolvePath = () => {
+    // resolves against the saved settings, unsaved rows are not used
+    invoke<ResolvedPath>('resolve_path', { path: testPath })
+        .then((data) => {
+          setResolvedPath(data);
+        })
+        .catch((error) => {
+          console.error("Error resolving path:", error);
+          pushMessage("Error resolving path: " + errorMessage(error), "warning");
+        });
+  };
+
   const navigate = useNavigate();
 
+  const pushMessage = (message: string, type: "success" | "warning") => {
+    setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
+  };
+
+  const loadProfiles = () => {
+    invoke<SettingsProfiles>('list_profiles', { })
+        .then((data) => {
+          setProfiles(data);
+          setProfileName(data.active ?? '');
+        })
+        .catch((error) => {
+          console.error("Error fetching profiles:", error);
+        });
+  };
+
+  useEffect(() => {
+    loadProfiles();
+  }, [])
+
+  const handleSwitchProfile = (name: string) => {
+    invoke<Settings>('switch_profile', { name: name })
+        .then((data) => {
+          setSettings(data);
+          loadProfiles();
+          pushMessage("Switched to profile " + name, "success");
+        })
+        .catch((error) => {
+          console.error("Error switching profile:", error);
+          pushMessage("Error switching profile: " + errorMessage(error), "warning");
+        });
+  };
+
+  const handleSaveProfile = () => {
+    invoke('save_profile', { name: profileName, settings: currentSettings })
+        .then(() => {
+          loadProfiles();
+          pushMessage("Saved profile " + profileName, "success");
+        })
+        .catch((error) => {
+          console.error("Error saving profile:", error);
+          pushMessage("Error saving profile: " + errorMessage(error), "warning");
+        });
+  };
+
+  const handleExport = () => {
+    invoke('export_settings', { path: bundlePath })
+        .then(() => {
+          pushMessage("Exported settings to " + bundlePath, "success");
+        })
+        .catch((error) => {
+          console.error("Error exporting settings:", error);
+          pushMessage("Error exporting settings: " + errorMessage(error), "warning");
+        });
+  };
+
+  const handleImport = () => {
+    invoke('import_settings', { path: bundlePath })
+        .then(() => {
+          handleUndo();
+          loadProfiles();
+          pushMessage("Imported settings from " + bundlePath, "success");
+        })
+        .catch((error) => {
+          console.error("Error importing settings:", error);
+          pushMessage("Error importing settings: " + errorMessage(error), "warning");
+        });
+  };
+
   useEffect(() => {
     invoke<Settings>('load_settings', { })
         .then((data) => {
@@ -67,7 +186,7 @@ function SettingsPage() {
         })
         .catch((error) => {
           console.error("Error fetching settings:", error); // throw out 
-          const message = "Error collecting settings: " + error;
+          const message = "Error collecting settings: " + errorMessage(error);
           const type = "warning";
           setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
         });
@@ -138,7 +257,7 @@ function SettingsPage() {
         })
         .catch((error) => {
             console.error("Error saving settings:", error);
-            const message = "Error saving settings: " + error;
+            const message = "Error saving settings: " + errorMessage(error);
             const type = "warning";
             setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
         });
@@ -152,7 +271,7 @@ function SettingsPage() {
         })
         .catch((error) => {
             console.error("Error fetching settings:", error);
-            const message = "Error undoing settings: " + error;
+            const message = "Error undoing settings: " + errorMessage(error);
             const type = "warning";
             setSnackPack((prev) => [...prev, { message, type, key: new Date().getTime() }]);
         });
@@ -253,6 +372,83 @@ function SettingsPage() {
                 error={!!errors.clr_printer}
               />
             </Box>
+            <Box sx={{p: '0.5em'}}>
+              <TextField 
+                id="fallback-printer-textfield" 
+                label="Fallback Printer" 
+                variant="outlined" 
+                value={currentSettings.fallback_printer}
+                onChange={handlePrinterChange('fallback_printer')}
+                helperText={currentSettings.fallback_printer ? errors.fallback_printer : "Used when Windows has no default printer"}
+                error={!!currentSettings.fallback_printer && !!errors.fallback_printer}
+              />
+            </Box>
+            <Box sx={{p: '0.5em'}}>
+              <TextField 
+                id="supervisors-textfield" 
+                label="Supervisor Initials" 
+                variant="outlined" 
+                value={currentSettings.supervisors.join(', ')}
+                onChange={(event: React.ChangeEvent<HTMLInputElement>) => {
+                  // blank entries are ignored when printing, keeping them lets a trailing comma be typed
+                  const supervisors = event.target.value.split(',').map((s) => s.trim());
+                  setCurrentSettings(prev => prev ? { ...prev, supervisors: supervisors } : prev);
+                }}
+                helperText="Can print past an order's due quantity"
+              />
+            </Box>
+            <Box sx={{p: '0.5em'}}>
+              <TextField
+                id="fractional-quantity-select"
+                select
+                label="Fractional Quantities"
+                variant="outlined"
+                value={currentSettings.fractional_quantity}
+                onChange={(event: React.ChangeEvent<HTMLInputElement>) => {
+                  setCurrentSettings(prev => prev ? { ...prev, fractional_quantity: event.target.value as Settings["fractional_quantity"] } : prev);
+                }}
+                sx={{width: 200}}
+              >
+                <MenuItem value="reject">Refuse to print</MenuItem>
+                <MenuItem value="round_up">Round up</MenuItem>
+                <MenuItem value="round_down">Round down</MenuItem>
+              </TextField>
+            </Box>
+            <Box sx={{p: '0.5em'}}>
+              <TextField
+                id="check-digit-select"
+                select
+                label="Serial Check Digit"
+                variant="outlined"
+                value={currentSettings.check_digit}
+                onChange={(event: React.ChangeEvent<HTMLInputElement>) => {
+                  setCurrentSettings(prev => prev ? { ...prev, check_digit: event.target.value as Settings["check_digit"] } : prev);
+                }}
+                sx={{width: 200}}
+              >
+                <MenuItem value="none">None</MenuItem>
+                <MenuItem value="mod10">Mod 10 (GS1)</MenuItem>
+                <MenuItem value="luhn">Luhn</MenuItem>
+                <MenuItem value="mod43">Mod 43 (Code 39)</MenuItem>
+              </TextField>
+            </Box>
+            <Box sx={{p: '0.5em'}}>
+              <TextField
+                id="log-level-select"
+                select
+                label="Log Level"
+                variant="outlined"
+                value={currentSettings.log_level}
+                onChange={(event: React.ChangeEvent<HTMLInputElement>) => {
+                  setCurrentSettings(prev => prev ? { ...prev, log_level: event.target.value } : prev);
+                }}
+                sx={{width: 200}}
+              >
+                {["OFF", "ERROR", "WARN", "INFO", "DEBUG", "TRACE"].map((level) => (
+                  <MenuItem key={level} value={level}>{level}</MenuItem>
+                ))}
+              </TextField>
+            </Box>
             <Box sx={{p: '0.5em'}}>
               <TextField 
                 id="PDF-to-printer-path-textfield" 
@@ -264,6 +460,19 @@ function SettingsPage() {
                 }}
               />
             </Box>
+            <Box sx={{p: '0.5em'}}>
+              <TextField 
+                id="archive-path-textfield" 
+                label="PDF Archive Folder" 
+                variant="outlined" 
+                autoComplete="off"
+                value={currentSettings.archive_path}
+                onChange={(event: React.ChangeEvent<HTMLInputElement>) => {
+                  setCurrentSettings(prev => prev ? { ...prev, archive_path: event.target.value } : prev);
+                }}
+                helperText="Empty turns archiving off"
+              />
+            </Box>
           </Box>
           <Box sx={{ display: 'flex', flexDirection: 'column',  alignItems: 'center', justifyContent: 'space-between', gap: '1em'}}>
             <Box sx={{p: '0.5em'}}>
@@ -377,6 +586,29 @@ function SettingsPage() {
               />
             </Box>
           </Box>
+          <Box sx={{ display: 'flex', flexDirection: 'column',  alignItems: 'center', justifyContent: 'space-between', gap: '1em'}}>
+            {/* address of the Zebra behind each shared label printer, for the status check before printing */}
+            {([
+              ["label_printer_125_025", "1.25 by 0.25"],
+              ["label_printer_2_025", "2 by 0.25"],
+              ["label_printer_2_3", "2 by 3"],
+              ["label_printer_075_025", "0.75 by 0.25"],
+              ["label_printer_4_6", "4 by 6"],
+            ]).map(([key, size]) => (
+              <Box key={key} sx={{p: '0.5em'}}>
+                <TextField 
+                  id={key + "-zebra-host-textfield"}
+                  label={size + " Zebra Address"} 
+                  variant="outlined" 
+                  autoComplete="off"
+                  value={currentSettings.zebra_hosts[key] || ''}
+                  onChange={(event: React.ChangeEvent<HTMLInputElement>) => {
+                    setCurrentSettings(prev => prev ? { ...prev, zebra_hosts: { ...prev.zebra_hosts, [key]: event.target.value } } : prev);
+                  }}
+                />
+              </Box>
+            ))}
+          </Box>
           <Paper elevation={1} sx={{ p: '1.5em', pb: '0.5em', maxWidth: '25em',  mx: 'auto', minHeight: '28em' }}>
                 Labels to Omit
               {/* Add New Item */}
@@ -423,6 +655,61 @@ function SettingsPage() {
               </List>
               </Box>
           </Paper>
+          <Paper elevation={1} sx={{ p: '1.5em', pb: '0.5em', maxWidth: '30em',  mx: 'auto', minHeight: '28em' }}>
+                Drive Mapping
+              <Box sx={{maxHeight: '16em', overflowY: 'auto', mb: '1em'}}>
+                {currentSettings.drive_map.map((mapping, index) => (
+                  <Box key={index} sx={{ display: 'flex', gap: '0.5em', mt: '0.5em', alignItems: 'center' }}>
+                    <TextField
+                      label="Drive"
+                      size="small"
+                      autoComplete="off"
+                      value={mapping.drive}
+                      onChange={handleDriveChange(index, 'drive')}
+                      sx={{width: '5em'}}
+                    />
+                    <TextField
+                      label="Server Path"
+                      size="small"
+                      autoComplete="off"
+                      value={mapping.unc}
+                      onChange={handleDriveChange(index, 'unc')}
+                      fullWidth
+                    />
+                    <IconButton aria-label="delete" onClick={() => handleDriveRemove(index)}>
+                      <DeleteIcon />
+                    </IconButton>
+                  </Box>
+                ))}
+                <IconButton color="primary" onClick={handleDriveAdd} aria-label="add">
+                  <AddIcon />
+                </IconButton>
+              </Box>
+
+              <Divider />
+
+              <Box sx={{ display: 'flex', gap: '0.5em', mt: '1em', alignItems: 'center' }}>
+                <TextField
+                  fullWidth
+                  size="small"
+                  label="Test path"
+                  autoComplete="off"
+                  value={testPath}
+                  onChange={(e) => setTestPath(e.target.value)}
+                  onKeyDown={(e) => e.key === 'Enter' && handleResolvePath()}
+                />
+                <Button variant="outlined" onClick={handleResolvePath} disabled={!testPath.trim()}>
+                  Resolve
+                </Button>
+              </Box>
+              {resolvedPath && (
+                <Typography variant="body2" sx={{ mt: 1, color: resolvedPath.exists ? 'text.secondary' : 'warning.main', wordBreak: 'break-all' }}>
+                  {resolvedPath.resolved}
+                  {resolvedPath.drive ? " (via " + resolvedPath.drive + ")" : " (no mapping)"}
+                  {resolvedPath.exists ? "" : " - not found"}
+                </Typography>
+              )}
+          </Paper>
         </Box>
         ) : (
           <Box sx={{ display: 'flex', flexDirection: 'row',  alignItems: 'center', justifyContent: 'center', gap: '1em', height: '100%', minHeight: '26em'}}>
@@ -431,7 +718,48 @@ function SettingsPage() {
           </Box>
         )}
 
-        
+
+        <Box sx={{ display: 'flex', flexDirection: 'row',  alignItems: 'center', justifyContent: 'center', gap: '1em'}}>
+          <TextField
+            id="profile-select"
+            select
+            label="Profile"
+            variant="outlined"
+            value={profiles?.active ?? ''}
+            onChange={(event: React.ChangeEvent<HTMLInputElement>) => handleSwitchProfile(event.target.value)}
+            sx={{width: 200}}
+          >
+            {profiles?.profiles.map((profile) => (
+              <MenuItem key={profile.name} value={profile.name}>{profile.name}</MenuItem>
+            ))}
+          </TextField>
+          <TextField
+            id="profile-name-textfield"
+            label="Profile Name"
+            variant="outlined"
+            autoComplete="off"
+            value={profileName}
+            onChange={(event: React.ChangeEvent<HTMLInputElement>) => setProfileName(event.target.value)}
+          />
+          <Button id="save-profile-button" variant="outlined" onClick={handleSaveProfile} disabled={!profileName.trim()}>
+            Save Profile
+          </Button>
+          <TextField
+            id="bundle-path-textfield"
+            label="Settings File"
+            variant="outlined"
+            autoComplete="off"
+            value={bundlePath}
+            onChange={(event: React.ChangeEvent<HTMLInputElement>) => setBundlePath(event.target.value)}
+          />
+          <Button id="export-button" variant="outlined" onClick={handleExport} disabled={!bundlePath}>
+            Export
+          </Button>
+          <Button id="import-button" variant="outlined" onClick={handleImport} disabled={!bundlePath}>
+            Import
+          </Button>
+        </Box>
+
         <Box sx={{ display: 'flex', flexDirection: 'row',  alignItems: 'center', justifyContent: 'space-around', gap: '1em', height: '20%'}}> 
           <Button 
             id="save-button" 
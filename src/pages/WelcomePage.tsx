@@ -3,7 +3,7 @@ import lightLogo from "./../assets/PixusLogoHD.png";
 import darkLogo from "./../assets/PixusLogoHDDarkmode.png";
 import { invoke } from "@tauri-apps/api/core";
 import "./../App.css";
-import { Box, Button,  CircularProgress,  FormControlLabel,  FormGroup,  Paper, Stack, Switch, Table, TableBody, TableCell, TableContainer, TableHead, TablePagination, TableRow, TextField, Typography, useTheme } from "@mui/material";
+import { Box, Button,  Chip,  CircularProgress,  FormControlLabel,  FormGroup,  MenuItem,  Paper, Stack, Switch, Table, TableBody, TableCell, TableContainer, TableHead, TablePagination, TableRow, TableSortLabel, TextField, Typography, useTheme } from "@mui/material";
 import Layout from './../Layout';
 import { useNavigate } from "react-router-dom";
 import SettingsIcon from '@mui/icons-material/Settings';
@@ -14,6 +14,26 @@ type Order = {
   part_number: string;
   due_quantity: number;
   assn_number: string;
+  due_date: string | null;
+  release_date: string | null;
+  customer: string;
+  planner: string;
+  remaining_quantity: number;
+};
+
+// YYYY-MM-DD in local time, the same form as the due dates
+const today = () => {
+  const now = new Date();
+  return new Date(now.getTime() - now.getTimezoneOffset() * 60000).toISOString().slice(0, 10);
+};
+
+type OrderSort = "order_number" | "part_number" | "due_date" | "due_quantity";
+
+type OrderPage = {
+  orders: Order[];
+  total: number;
+  page: number;
+  page_size: number;
 };
 
 function WelcomePage() {
@@ -21,12 +41,18 @@ function WelcomePage() {
 
   const orderNumValid = orderNumber.length >= 8;
   const [orders, setOrders] = useState<Order[] | null>(null);
-  
-  const [ordersFiltered, setOrdersFiltered] = useState<Order[] | null>(null);
+  const [total, setTotal] = useState(0);
 
   const [page, setPage] = useState(0);
   const rowsPerPage = 5 ;
 
+  const [search, setSearch] = useState("");
+  const [family, setFamily] = useState("");
+  const [dueFrom, setDueFrom] = useState("");
+  const [dueTo, setDueTo] = useState("");
+  const [sort, setSort] = useState<OrderSort>("order_number");
+  const [descending, setDescending] = useState(true);
+
   const theme = useTheme();
   const isDarkMode = theme.palette.mode === 'dark';
 
@@ -36,9 +62,21 @@ function WelcomePage() {
 
   const handleCheck5xx = (event: React.ChangeEvent<HTMLInputElement>) => {
     setCheck5xx(event.target.checked);
+    setPage(0);
   };
   const handleCheck7xx = (event: React.ChangeEvent<HTMLInputElement>) => {
     setCheck7xx(event.target.checked);
+    setPage(0);
+  };
+
+  const handleSort = (column: OrderSort) => {
+    if (sort === column) {
+      setDescending(!descending);
+    } else {
+      setSort(column);
+      setDescending(column === "order_number");
+    }
+    setPage(0);
   };
 
 
@@ -46,15 +84,6 @@ function WelcomePage() {
   const navigate = useNavigate();
 
   useEffect(() => {
-    invoke<Order[]>('get_orders', { })
-        .then((data) => {
-          setOrders(data);
-          console.log(data)
-        })
-        .catch((error) => {
-            console.error("Error fetching orders:", error);
-        });
-    
     invoke<String>('reset_serial_check', { })
         .then((data) => {
           console.log(data)
@@ -65,19 +94,38 @@ function WelcomePage() {
   }, [])
 
   useEffect(() => {
-    updateFilteredOrders();
-  }, [orders])
-
-  useEffect(() => {
-    updateFilteredOrders();
-  }, [check5xx, check7xx])
-
-  const updateFilteredOrders = () => {
-    if (orders){
-      const remainingOrders = orders.filter((order) => (order.order_number.startsWith("5") && check5xx) || (order.order_number.startsWith("7") && check7xx) );
-      setOrdersFiltered(remainingOrders);
+    const series = [check5xx ? "5" : "", check7xx ? "7" : ""].filter((s) => s);
+    if (series.length === 0) {
+      setOrders([]);
+      setTotal(0);
+      return;
     }
-  }
+    // wait for typing to settle before asking the ERP
+    const timer = setTimeout(() => {
+      invoke<OrderPage>('get_orders', {
+        query: {
+          search: search,
+          status: null,
+          due_from: dueFrom || null,
+          due_to: dueTo || null,
+          series: series,
+          families: family ? [family] : [],
+          sort: sort,
+          descending: descending,
+          page: page,
+          page_size: rowsPerPage,
+        },
+      })
+        .then((data) => {
+          setOrders(data.orders);
+          setTotal(data.total);
+        })
+        .catch((error) => {
+            console.error("Error fetching orders:", error);
+        });
+    }, 300);
+    return () => clearTimeout(timer);
+  }, [check5xx, check7xx, search, family, dueFrom, dueTo, sort, descending, page])
 
   const handleChangePage = (_event: unknown, newPage: number) => {
     setPage(newPage);
@@ -116,7 +164,7 @@ function WelcomePage() {
           <SettingsIcon  onClick={handleSettings} sx={{ cursor: 'pointer', fontSize: '2em', marginLeft: '3em' }} />
         </Box>
 
-        { ordersFiltered ? (
+        { orders ? (
           <Box 
             sx={{ 
               display: 'flex', 
@@ -130,19 +178,68 @@ function WelcomePage() {
                 <FormControlLabel control={<Switch checked={check5xx} onChange={handleCheck5xx} />} label="5xx's" />
                 <FormControlLabel control={<Switch checked={check7xx} onChange={handleCheck7xx} />} label="7xx's" />
               </FormGroup>
+              <TextField
+                label="Search"
+                size="small"
+                autoComplete="off"
+                value={search}
+                onChange={(event) => { setSearch(event.target.value); setPage(0); }}
+              />
+              <TextField
+                select
+                label="Family"
+                size="small"
+                value={family}
+                onChange={(event) => { setFamily(event.target.value); setPage(0); }}
+              >
+                <MenuItem value="">All</MenuItem>
+                <MenuItem value="02">02</MenuItem>
+                <MenuItem value="K02">K02</MenuItem>
+              </TextField>
+              <TextField
+                label="Due from"
+                type="date"
+                size="small"
+                value={dueFrom}
+                onChange={(event) => { setDueFrom(event.target.value); setPage(0); }}
+                slotProps={{ inputLabel: { shrink: true } }}
+              />
+              <TextField
+                label="Due to"
+                type="date"
+                size="small"
+                value={dueTo}
+                onChange={(event) => { setDueTo(event.target.value); setPage(0); }}
+                slotProps={{ inputLabel: { shrink: true } }}
+              />
             </Stack>
             <Box sx={{minHeight: '25em'}}> 
-              <Box sx={{ alignItems: 'center',  width: '30em'}}>
+              <Box sx={{ alignItems: 'center',  width: '45em'}}>
                 <TableContainer component={Paper}>
                     <Table>
                         <TableHead>
                         <TableRow>
-                            <TableCell>Order Number</TableCell>
-                            <TableCell>Part Number</TableCell>
+                            <TableCell>
+                              <TableSortLabel active={sort === "order_number"} direction={descending ? "desc" : "asc"} onClick={() => handleSort("order_number")}>
+                                Order Number
+                              </TableSortLabel>
+                            </TableCell>
+                            <TableCell>
+                              <TableSortLabel active={sort === "part_number"} direction={descending ? "desc" : "asc"} onClick={() => handleSort("part_number")}>
+                                Part Number
+                              </TableSortLabel>
+                            </TableCell>
+                            <TableCell>Customer</TableCell>
+                            <TableCell>Left</TableCell>
+                            <TableCell>
+                              <TableSortLabel active={sort === "due_date"} direction={descending ? "desc" : "asc"} onClick={() => handleSort("due_date")}>
+                                Due
+                              </TableSortLabel>
+                            </TableCell>
                         </TableRow>
                         </TableHead>
                         <TableBody>
-                        {ordersFiltered.slice(page * rowsPerPage, page * rowsPerPage + rowsPerPage).map((order, index) => (
+                        {orders.map((order, index) => (
                             <TableRow 
                               key={index}
                               hover
@@ -153,6 +250,14 @@ function WelcomePage() {
                             >
                             <TableCell>{order.order_number}</TableCell>
                             <TableCell>{order.part_number}</TableCell>
+                            <TableCell>{order.customer}</TableCell>
+                            <TableCell>{order.remaining_quantity}</TableCell>
+                            <TableCell>
+                              {order.due_date ?? ""}
+                              {order.due_date && order.due_date < today() && order.remaining_quantity > 0 && (
+                                <Chip label="Overdue" color="error" size="small" sx={{ ml: 1 }} />
+                              )}
+                            </TableCell>
                             </TableRow>
                         ))}
                         </TableBody>
@@ -162,7 +267,7 @@ function WelcomePage() {
               <TablePagination
                 rowsPerPageOptions={[5]}
                 component="div"
-                count={ordersFiltered.length}
+                count={total}
                 rowsPerPage={rowsPerPage}
                 page={page}
                 onPageChange={handleChangePage}